@@ -0,0 +1,330 @@
+/*
+ * Alarm model shared by the TUI and the GUI.
+ */
+
+use crate::audio::Sound;
+use crate::config::{AlarmEntry, DstPolicy, GapPolicy, OverlapPolicy, Repeat};
+use crate::cron::CronExpr;
+use crate::zones;
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Timelike,
+    Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+/// The zone alarms without one of their own are read in.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LocalZone {
+    /// The system's local time.
+    #[default]
+    System,
+    /// A zone standing in for local time, so results do not depend on the
+    /// machine's.
+    Zone(Tz),
+}
+
+impl LocalZone {
+    /// Wall-clock time at `at`.
+    pub fn wall(&self, at: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            LocalZone::System => at.with_timezone(&Local).naive_local(),
+            LocalZone::Zone(tz) => at.with_timezone(tz).naive_local(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Alarm {
+    pub id: u32,
+    pub label: Option<String>,
+    pub message: Option<String>,
+    /// Time of day the alarm fires; ignored by cron schedules.
+    pub time: NaiveTime,
+    /// Zone the time is given in; `None` means the system's local time.
+    pub zone: Option<Tz>,
+    pub schedule: Schedule,
+    pub dst: DstPolicy,
+    pub sound: Sound,
+    /// Shell command to run when the alarm fires.
+    pub command: Option<String>,
+}
+
+/// Parsed form of `config::Repeat`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Schedule {
+    Daily,
+    Weekdays,
+    Days(Vec<Weekday>),
+    Once(NaiveDate),
+    Every { minutes: u32, until: NaiveTime },
+    Cron(CronExpr),
+}
+
+impl Schedule {
+    pub fn from_repeat(repeat: &Repeat) -> Result<Schedule, String> {
+        Ok(match repeat {
+            Repeat::Daily => Schedule::Daily,
+            Repeat::Weekdays => Schedule::Weekdays,
+            Repeat::Days { days } => Schedule::Days(
+                days.iter()
+                    .map(|d| d.parse::<Weekday>().map_err(|_| format!("Invalid day: {}", d)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Repeat::Once { date } => Schedule::Once(
+                NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", date))?,
+            ),
+            Repeat::Every { minutes, until } => {
+                if *minutes == 0 {
+                    return Err("Alarm interval must be at least one minute".to_string());
+                }
+                Schedule::Every {
+                    minutes: *minutes,
+                    until: NaiveTime::parse_from_str(until, "%H:%M")
+                        .map_err(|_| format!("Invalid alarm format: {}", until))?,
+                }
+            }
+            Repeat::Cron { expr } => Schedule::Cron(CronExpr::parse(expr)?),
+        })
+    }
+
+    /// Whether an alarm at `time` with this schedule fires in the minute
+    /// containing `wall` (a wall-clock time in the alarm's zone).
+    fn fires_at(&self, time: NaiveTime, wall: NaiveDateTime) -> bool {
+        let same_minute = |t: NaiveTime| wall.hour() == t.hour() && wall.minute() == t.minute();
+        match self {
+            Schedule::Daily => same_minute(time),
+            Schedule::Weekdays => {
+                !matches!(wall.weekday(), Weekday::Sat | Weekday::Sun) && same_minute(time)
+            }
+            Schedule::Days(days) => days.contains(&wall.weekday()) && same_minute(time),
+            Schedule::Once(date) => wall.date() == *date && same_minute(time),
+            Schedule::Every { minutes, until } => {
+                let start = time.hour() * 60 + time.minute();
+                let end = until.hour() * 60 + until.minute();
+                let current = wall.hour() * 60 + wall.minute();
+                (start..=end).contains(&current) && (current - start).is_multiple_of(*minutes)
+            }
+            Schedule::Cron(expr) => expr.matches(wall),
+        }
+    }
+}
+
+impl Alarm {
+    pub fn from_entry(entry: &AlarmEntry) -> Result<Alarm, String> {
+        let schedule = Schedule::from_repeat(&entry.repeat)?;
+        let time = match schedule {
+            Schedule::Cron(_) => NaiveTime::MIN,
+            _ => NaiveTime::parse_from_str(&entry.time, "%H:%M")
+                .map_err(|_| format!("Invalid alarm format: {}", entry.time))?,
+        };
+        if let Schedule::Every { until, .. } = schedule
+            && until < time
+        {
+            return Err(format!("Alarm window ends ({}) before it starts ({})", until.format("%H:%M"), entry.time));
+        }
+        let zone = match &entry.zone {
+            Some(zone) => Some(zones::resolve(zone).map_err(|e| e.to_string())?),
+            None => None,
+        };
+        Ok(Alarm {
+            id: entry.id,
+            label: entry.label.clone(),
+            message: entry.message.clone(),
+            time,
+            zone,
+            schedule,
+            dst: entry.dst,
+            sound: Sound::from_entry(entry.sound.as_deref()),
+            command: entry.command.clone(),
+        })
+    }
+
+    /// Whether `now` falls in a minute the alarm fires in, read in the alarm's
+    /// zone or else in `local`.
+    pub fn is_ringing(&self, now: DateTime<Utc>, local: LocalZone) -> bool {
+        match (self.zone, local) {
+            (Some(tz), _) | (None, LocalZone::Zone(tz)) => self.rings_in(&tz, now),
+            (None, LocalZone::System) => self.rings_in(&Local, now),
+        }
+    }
+
+    /// Matches the schedule against wall-clock time in `tz`, applying the DST
+    /// policy to times a transition skips or repeats.
+    fn rings_in<Z: TimeZone>(&self, tz: &Z, now: DateTime<Utc>) -> bool {
+        let local = now.with_timezone(tz);
+        let wall = local.naive_local();
+        if self.schedule.fires_at(self.time, wall) {
+            // On a fall-back day the wall time comes round twice.
+            return match tz.from_local_datetime(&wall) {
+                LocalResult::Ambiguous(first, last) => match self.dst.overlap {
+                    OverlapPolicy::First => local.offset().fix() == first.offset().fix(),
+                    OverlapPolicy::Last => local.offset().fix() == last.offset().fix(),
+                    OverlapPolicy::Both => true,
+                },
+                _ => true,
+            };
+        }
+
+        // On a spring-forward day, times inside the gap fire in the first
+        // minute after it.
+        if self.dst.gap == GapPolicy::Skip {
+            return false;
+        }
+        let minute = |t: NaiveDateTime| t.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(t);
+        let previous = minute((now - Duration::minutes(1)).with_timezone(tz).naive_local());
+        let mut skipped = previous + Duration::minutes(1);
+        let current = minute(wall);
+        while skipped < current {
+            if self.schedule.fires_at(self.time, skipped) {
+                return true;
+            }
+            skipped += Duration::minutes(1);
+        }
+        false
+    }
+
+    /// Short form for tiles: "09:00", or the cron expression.
+    pub fn short(&self) -> String {
+        match &self.schedule {
+            Schedule::Cron(expr) => format!("cron {}", expr),
+            _ => self.time.format("%H:%M").to_string(),
+        }
+    }
+
+    /// What to call the alarm when it fires: its label, or "Alarm 09:00".
+    pub fn title(&self) -> String {
+        match &self.label {
+            Some(label) => label.clone(),
+            None => format!("Alarm {}", self.short()),
+        }
+    }
+
+    /// The time together with its recurrence, e.g. "09:00 weekdays".
+    pub fn summary(&self) -> String {
+        let time = self.time.format("%H:%M");
+        match &self.schedule {
+            Schedule::Daily => time.to_string(),
+            Schedule::Weekdays => format!("{} weekdays", time),
+            Schedule::Days(days) => {
+                let days: Vec<String> = days.iter().map(|d| d.to_string()).collect();
+                format!("{} {}", time, days.join(","))
+            }
+            Schedule::Once(date) => format!("{} on {}", time, date),
+            Schedule::Every { minutes, until } => {
+                format!("{}-{} every {}m", time, until.format("%H:%M"), minutes)
+            }
+            Schedule::Cron(expr) => format!("cron {}", expr),
+        }
+    }
+}
+
+/// Parses a `[label=]HH:MM[@Zone]` CLI argument into a config entry. The zone
+/// may be anything `zones::resolve` understands and is stored as its IANA name.
+pub fn parse_alarm_arg(arg: &str) -> Result<AlarmEntry, String> {
+    let (label, spec) = match arg.rsplit_once('=') {
+        Some((label, spec)) if !label.trim().is_empty() => (Some(label.trim().to_string()), spec),
+        Some((_, spec)) => (None, spec),
+        None => (None, arg),
+    };
+    let (time, zone) = match spec.split_once('@') {
+        Some((time, zone)) => (time.trim(), Some(zone)),
+        None => (spec.trim(), None),
+    };
+    let time = NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| format!("Invalid alarm format: {}", arg))?;
+    let zone = match zone {
+        Some(zone) => Some(zones::resolve(zone).map_err(|e| e.to_string())?.name().to_string()),
+        None => None,
+    };
+    Ok(AlarmEntry { label, time: time.format("%H:%M").to_string(), zone, ..AlarmEntry::default() })
+}
+
+#[cfg(test)]
+impl Alarm {
+    /// A daily 09:00 local alarm for tests, after `edit` has adjusted the
+    /// entry it is built from.
+    pub(crate) fn for_test(id: u32, label: &str, edit: impl FnOnce(&mut AlarmEntry)) -> Alarm {
+        let mut entry =
+            AlarmEntry { id, label: Some(label.to_string()), time: "09:00".to_string(), ..AlarmEntry::default() };
+        edit(&mut entry);
+        Alarm::from_entry(&entry).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::New_York;
+
+    fn alarm(time: &str, repeat: Repeat, gap: GapPolicy, overlap: OverlapPolicy) -> Alarm {
+        Alarm::for_test(1, "Early", |entry| {
+            entry.time = time.to_string();
+            entry.zone = Some("America/New_York".to_string());
+            entry.repeat = repeat;
+            entry.dst = DstPolicy { gap, overlap };
+        })
+    }
+
+    /// Every minute (in UTC) the alarm rings in over the New York day `date`.
+    fn firings(alarm: &Alarm, date: &str) -> Vec<String> {
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        let midnight = |day: NaiveDate| New_York.from_local_datetime(&day.and_time(NaiveTime::MIN)).unwrap();
+        let (start, end) = (midnight(day), midnight(day.succ_opt().unwrap()));
+        (0..(end - start).num_minutes())
+            .map(|minute| start.with_timezone(&Utc) + Duration::minutes(minute))
+            .filter(|at| alarm.is_ringing(*at, LocalZone::System))
+            .map(|at| at.format("%H:%M").to_string())
+            .collect()
+    }
+
+    // 2026-03-08: New York goes from 02:00 EST straight to 03:00 EDT (07:00 UTC).
+    const SPRING_FORWARD: &str = "2026-03-08";
+    // 2026-11-01: 02:00 EDT goes back to 01:00 EST (06:00 UTC), so 01:00-01:59 happens twice.
+    const FALL_BACK: &str = "2026-11-01";
+
+    #[test]
+    fn time_in_the_gap_fires_right_after_it() {
+        let alarm = alarm("02:30", Repeat::Daily, GapPolicy::Next, OverlapPolicy::First);
+        assert_eq!(firings(&alarm, SPRING_FORWARD), ["07:00"]);
+    }
+
+    #[test]
+    fn time_in_the_gap_can_be_skipped() {
+        let alarm = alarm("02:30", Repeat::Daily, GapPolicy::Skip, OverlapPolicy::First);
+        assert!(firings(&alarm, SPRING_FORWARD).is_empty());
+    }
+
+    #[test]
+    fn repeated_time_fires_on_the_chosen_pass() {
+        let first = alarm("01:30", Repeat::Daily, GapPolicy::Next, OverlapPolicy::First);
+        assert_eq!(firings(&first, FALL_BACK), ["05:30"]);
+        let last = alarm("01:30", Repeat::Daily, GapPolicy::Next, OverlapPolicy::Last);
+        assert_eq!(firings(&last, FALL_BACK), ["06:30"]);
+        let both = alarm("01:30", Repeat::Daily, GapPolicy::Next, OverlapPolicy::Both);
+        assert_eq!(firings(&both, FALL_BACK), ["05:30", "06:30"]);
+    }
+
+    #[test]
+    fn interval_across_the_gap_fires_once_after_it() {
+        let every = Repeat::Every { minutes: 15, until: "04:00".to_string() };
+        let alarm = alarm("01:00", every, GapPolicy::Next, OverlapPolicy::First);
+        assert_eq!(
+            firings(&alarm, SPRING_FORWARD),
+            ["06:00", "06:15", "06:30", "06:45", "07:00", "07:15", "07:30", "07:45", "08:00"]
+        );
+    }
+
+    #[test]
+    fn cron_in_the_gap_follows_the_gap_policy() {
+        let cron = || Repeat::Cron { expr: "30 2 * * *".to_string() };
+        let next = alarm("", cron(), GapPolicy::Next, OverlapPolicy::First);
+        assert_eq!(firings(&next, SPRING_FORWARD), ["07:00"]);
+        let skip = alarm("", cron(), GapPolicy::Skip, OverlapPolicy::First);
+        assert!(firings(&skip, SPRING_FORWARD).is_empty());
+    }
+
+    #[test]
+    fn ordinary_days_are_unaffected() {
+        let alarm = alarm("02:30", Repeat::Daily, GapPolicy::Skip, OverlapPolicy::Both);
+        assert_eq!(firings(&alarm, "2026-03-09"), ["06:30"]);
+    }
+}
@@ -0,0 +1,87 @@
+/*
+ * Analog clock face for the GUI, drawn on an iced canvas. The dial is light
+ * during the day and dark at night in the clock's own zone.
+ */
+
+use chrono::{NaiveTime, Timelike};
+use iced::{
+    mouse,
+    widget::canvas::{self, stroke, Frame, Geometry, LineCap, Path, Stroke},
+    Color, Point, Rectangle, Renderer, Theme, Vector,
+};
+use std::f32::consts::TAU;
+
+/// Hours counted as daytime for the dial tint.
+const DAY_HOURS: std::ops::Range<u32> = 6..18;
+
+pub struct AnalogFace {
+    /// Wall-clock time in the clock's zone.
+    pub time: NaiveTime,
+}
+
+impl AnalogFace {
+    fn is_day(&self) -> bool {
+        DAY_HOURS.contains(&self.time.hour())
+    }
+}
+
+impl<Message> canvas::Program<Message> for AnalogFace {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let center = frame.center();
+        let radius = frame.width().min(frame.height()) / 2.0 - 1.0;
+
+        let (dial, ink) = if self.is_day() {
+            (Color::from_rgb(0.93, 0.88, 0.72), Color::from_rgb(0.1, 0.1, 0.1))
+        } else {
+            (Color::from_rgb(0.08, 0.1, 0.28), Color::from_rgb(0.85, 0.88, 0.95))
+        };
+        let line = |width: f32, color: Color| Stroke {
+            width,
+            style: stroke::Style::Solid(color),
+            line_cap: LineCap::Round,
+            ..Stroke::default()
+        };
+
+        frame.fill(&Path::circle(center, radius), dial);
+        frame.stroke(&Path::circle(center, radius), line(radius / 40.0, Color::from_rgb(0.0, 1.0, 1.0)));
+
+        frame.translate(Vector::new(center.x, center.y));
+
+        // Hour marks, longer at 12, 3, 6 and 9.
+        for hour in 0..12 {
+            let inner = if hour % 3 == 0 { 0.78 } else { 0.86 };
+            frame.with_save(|frame| {
+                frame.rotate(TAU * hour as f32 / 12.0);
+                let mark = Path::line(Point::new(0.0, -inner * radius), Point::new(0.0, -0.94 * radius));
+                frame.stroke(&mark, line(radius / 50.0, ink));
+            });
+        }
+
+        let seconds = self.time.second() as f32;
+        let minutes = self.time.minute() as f32 + seconds / 60.0;
+        let hours = (self.time.hour() % 12) as f32 + minutes / 60.0;
+        let hand = |frame: &mut Frame, turn: f32, length: f32, width: f32, color: Color| {
+            frame.with_save(|frame| {
+                frame.rotate(TAU * turn);
+                let path = Path::line(Point::new(0.0, 0.12 * radius), Point::new(0.0, -length * radius));
+                frame.stroke(&path, line(width, color));
+            });
+        };
+        hand(&mut frame, hours / 12.0, 0.5, radius / 14.0, ink);
+        hand(&mut frame, minutes / 60.0, 0.75, radius / 20.0, ink);
+        hand(&mut frame, seconds / 60.0, 0.85, radius / 60.0, Color::from_rgb(1.0, 0.3, 0.3));
+        frame.fill(&Path::circle(Point::ORIGIN, radius / 25.0), ink);
+
+        vec![frame.into_geometry()]
+    }
+}
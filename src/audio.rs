@@ -0,0 +1,260 @@
+/*
+ * Audible alarms. The TUI rings the terminal bell; the GUI plays a sound
+ * file through whichever command-line player the system has (there is no
+ * audio library dependency). Either can be swapped for `NullSink`, e.g. with
+ * `--mute`.
+ */
+
+use crate::alarm::Alarm;
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// Chime played for alarms that do not name their own sound file.
+const BUNDLED_SOUND: &[u8] = include_bytes!("../assets/alarm.wav");
+
+/// How often the bell repeats, or how soon a finished sound file restarts,
+/// while an alarm is ringing.
+const REPEAT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What an alarm sounds like, from `AlarmEntry::sound`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sound {
+    /// The frontend's usual signal: the bell, or the bundled chime.
+    Default,
+    /// Silent; only the banner shows the alarm.
+    Off,
+    /// A WAV/OGG file to play instead of the chime.
+    File(PathBuf),
+}
+
+impl Sound {
+    pub fn from_entry(sound: Option<&str>) -> Sound {
+        match sound {
+            None => Sound::Default,
+            Some(s) if s.eq_ignore_ascii_case("off") || s.eq_ignore_ascii_case("none") => Sound::Off,
+            Some(path) => Sound::File(PathBuf::from(path)),
+        }
+    }
+}
+
+/// Something that can make a noise.
+pub trait AudioSink {
+    /// Starts `sound` unless it is still playing from the last call.
+    fn play(&mut self, sound: &Sound);
+    /// Stops anything still playing.
+    fn stop(&mut self);
+}
+
+/// Discards everything.
+pub struct NullSink;
+
+impl AudioSink for NullSink {
+    fn play(&mut self, _sound: &Sound) {}
+    fn stop(&mut self) {}
+}
+
+/// Writes BEL to the terminal, whatever the sound; terminals cannot play files.
+pub struct TerminalBell;
+
+impl AudioSink for TerminalBell {
+    fn play(&mut self, _sound: &Sound) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+    }
+
+    fn stop(&mut self) {}
+}
+
+/// Plays files with an external player such as `paplay` or `afplay`.
+pub struct CommandPlayer {
+    program: &'static str,
+    args: &'static [&'static str],
+    /// Where the bundled chime was written, once it has been needed.
+    bundled: Option<PathBuf>,
+    child: Option<Child>,
+}
+
+/// Players to try, in order, with the arguments that precede the file.
+const PLAYERS: &[(&str, &[&str])] = &[
+    ("pw-play", &[]),
+    ("paplay", &[]),
+    ("ffplay", &["-nodisp", "-autoexit", "-loglevel", "quiet"]),
+    ("afplay", &[]),
+    ("aplay", &["-q"]),
+];
+
+/// The players `CommandPlayer::detect` looks for, for messages.
+pub fn player_names() -> String {
+    PLAYERS.iter().map(|(program, _)| *program).collect::<Vec<_>>().join(", ")
+}
+
+impl CommandPlayer {
+    /// The first known player found on `PATH`.
+    pub fn detect() -> Option<CommandPlayer> {
+        let path = env::var_os("PATH")?;
+        let dirs: Vec<PathBuf> = env::split_paths(&path).collect();
+        PLAYERS
+            .iter()
+            .find(|(program, _)| dirs.iter().any(|dir| dir.join(program).is_file()))
+            .map(|&(program, args)| CommandPlayer { program, args, bundled: None, child: None })
+    }
+
+    fn is_playing(&mut self) -> bool {
+        self.child.as_mut().is_some_and(|child| matches!(child.try_wait(), Ok(None)))
+    }
+
+    fn bundled_path(&mut self) -> io::Result<PathBuf> {
+        if let Some(path) = &self.bundled {
+            return Ok(path.clone());
+        }
+        let path = env::temp_dir().join(format!("rust-world-clock-{}.wav", std::process::id()));
+        fs::write(&path, BUNDLED_SOUND)?;
+        self.bundled = Some(path.clone());
+        Ok(path)
+    }
+
+    fn spawn(&self, file: &Path) -> io::Result<Child> {
+        Command::new(self.program)
+            .args(self.args)
+            .arg(file)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    }
+}
+
+impl AudioSink for CommandPlayer {
+    fn play(&mut self, sound: &Sound) {
+        if self.is_playing() {
+            return;
+        }
+        let file = match sound {
+            Sound::Off => return,
+            Sound::File(path) => Ok(path.clone()),
+            Sound::Default => self.bundled_path(),
+        };
+        self.child = file.and_then(|file| self.spawn(&file)).ok();
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for CommandPlayer {
+    fn drop(&mut self) {
+        self.stop();
+        if let Some(path) = &self.bundled {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Repeats the sound of whatever is ringing through a sink, and falls quiet
+/// when nothing is.
+pub struct Ringer {
+    sink: Box<dyn AudioSink>,
+    last_played: Option<Instant>,
+}
+
+impl Ringer {
+    pub fn new(sink: Box<dyn AudioSink>) -> Self {
+        Ringer { sink, last_played: None }
+    }
+
+    /// Call regularly with the alarms currently ringing. The first of them
+    /// that is not silent decides the sound.
+    pub fn update(&mut self, ringing: &[&Alarm]) {
+        let Some(sound) = ringing.iter().map(|alarm| &alarm.sound).find(|sound| **sound != Sound::Off) else {
+            if self.last_played.take().is_some() {
+                self.sink.stop();
+            }
+            return;
+        };
+        if self.last_played.is_none_or(|at| at.elapsed() >= REPEAT_INTERVAL) {
+            self.sink.play(sound);
+            self.last_played = Some(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    /// Keeps a log of what it was asked to do.
+    #[derive(Clone, Default)]
+    struct RecordingSink(Rc<RefCell<Vec<String>>>);
+
+    impl RecordingSink {
+        fn take(&self) -> Vec<String> {
+            std::mem::take(&mut self.0.borrow_mut())
+        }
+    }
+
+    impl AudioSink for RecordingSink {
+        fn play(&mut self, sound: &Sound) {
+            self.0.borrow_mut().push(format!("play {:?}", sound));
+        }
+
+        fn stop(&mut self) {
+            self.0.borrow_mut().push("stop".to_string());
+        }
+    }
+
+    fn alarm(sound: Option<&str>) -> Alarm {
+        Alarm::for_test(1, "Wake up", |entry| entry.sound = sound.map(str::to_string))
+    }
+
+    #[test]
+    fn plays_when_an_alarm_starts_and_repeats_after_the_interval() {
+        let sink = RecordingSink::default();
+        let mut ringer = Ringer::new(Box::new(sink.clone()));
+        let alarm = alarm(None);
+
+        ringer.update(&[&alarm]);
+        assert_eq!(sink.take(), ["play Default"]);
+        ringer.update(&[&alarm]);
+        assert!(sink.take().is_empty());
+
+        ringer.last_played = ringer.last_played.map(|at| at - REPEAT_INTERVAL);
+        ringer.update(&[&alarm]);
+        assert_eq!(sink.take(), ["play Default"]);
+    }
+
+    #[test]
+    fn silent_alarms_are_passed_over() {
+        let sink = RecordingSink::default();
+        let mut ringer = Ringer::new(Box::new(sink.clone()));
+        let (silent, chime) = (alarm(Some("off")), alarm(Some("chime.wav")));
+
+        ringer.update(&[&silent]);
+        assert!(sink.take().is_empty());
+        ringer.update(&[&silent, &chime]);
+        assert_eq!(sink.take(), ["play File(\"chime.wav\")"]);
+    }
+
+    #[test]
+    fn stops_once_nothing_rings() {
+        let sink = RecordingSink::default();
+        let mut ringer = Ringer::new(Box::new(sink.clone()));
+        let alarm = alarm(None);
+
+        ringer.update(&[]);
+        assert!(sink.take().is_empty());
+        ringer.update(&[&alarm]);
+        ringer.update(&[]);
+        ringer.update(&[]);
+        assert_eq!(sink.take(), ["play Default", "stop"]);
+    }
+}
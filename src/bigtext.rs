@@ -0,0 +1,97 @@
+/*
+ * Large block digits for the terminal UI's tiles. Each glyph is a 3x5
+ * bitmap, drawn as whole blocks at the biggest scale that fits the space
+ * given, or as half blocks (two pixels per cell, one above the other) when
+ * even the smallest whole-block size does not. Text with characters that
+ * have no glyph, or that does not fit either way, is left to the caller to
+ * show as plain text.
+ */
+
+const GLYPH_HEIGHT: usize = 5;
+
+/// Rows of each glyph, `#` for a set pixel.
+fn glyph(c: char) -> Option<[&'static str; GLYPH_HEIGHT]> {
+    Some(match c {
+        '0' => ["###", "#.#", "#.#", "#.#", "###"],
+        '1' => [".#.", "##.", ".#.", ".#.", "###"],
+        '2' => ["###", "..#", "###", "#..", "###"],
+        '3' => ["###", "..#", ".##", "..#", "###"],
+        '4' => ["#.#", "#.#", "###", "..#", "..#"],
+        '5' => ["###", "#..", "###", "..#", "###"],
+        '6' => ["###", "#..", "###", "#.#", "###"],
+        '7' => ["###", "..#", ".#.", ".#.", ".#."],
+        '8' => ["###", "#.#", "###", "#.#", "###"],
+        '9' => ["###", "#.#", "###", "..#", "###"],
+        ':' => [".", "#", ".", "#", "."],
+        '.' => [".", ".", ".", ".", "#"],
+        '-' => ["..", "..", "##", "..", ".."],
+        ' ' => [".", ".", ".", ".", "."],
+        'A' => [".#.", "#.#", "###", "#.#", "#.#"],
+        'P' => ["##.", "#.#", "##.", "#..", "#.."],
+        'M' => ["#.#", "###", "###", "#.#", "#.#"],
+        _ => return None,
+    })
+}
+
+/// Renders `text` in large digits within `width` x `height` cells, or
+/// `None` if it cannot be.
+pub fn render(text: &str, width: usize, height: usize) -> Option<Vec<String>> {
+    let pixels = bitmap(text)?;
+    let columns = pixels[0].len();
+
+    // Terminal cells are about twice as tall as they are wide, so a whole-block
+    // pixel is two cells wide per row of height.
+    let scale = (height / GLYPH_HEIGHT).min(width / (columns * 2));
+    if scale > 0 {
+        let lines = pixels
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&on| if on { "█" } else { " " }.repeat(scale * 2))
+                    .collect::<String>()
+            })
+            .flat_map(|line| std::iter::repeat_n(line, scale))
+            .collect();
+        return Some(lines);
+    }
+
+    if columns <= width && GLYPH_HEIGHT.div_ceil(2) <= height {
+        let lines = pixels
+            .chunks(2)
+            .map(|pair| {
+                (0..columns)
+                    .map(|x| match (pair[0][x], pair.get(1).is_some_and(|below| below[x])) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    })
+                    .collect()
+            })
+            .collect();
+        return Some(lines);
+    }
+
+    None
+}
+
+/// The pixels of `text`, a column apart between glyphs.
+fn bitmap(text: &str) -> Option<Vec<Vec<bool>>> {
+    let glyphs = text.chars().map(glyph).collect::<Option<Vec<_>>>()?;
+    if glyphs.is_empty() {
+        return None;
+    }
+    let rows = (0..GLYPH_HEIGHT)
+        .map(|y| {
+            let mut row = Vec::new();
+            for (i, glyph) in glyphs.iter().enumerate() {
+                if i > 0 {
+                    row.push(false);
+                }
+                row.extend(glyph[y].chars().map(|c| c == '#'));
+            }
+            row
+        })
+        .collect();
+    Some(rows)
+}
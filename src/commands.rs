@@ -0,0 +1,381 @@
+/*
+ * Non-interactive subcommands that edit or list the saved configuration.
+ */
+
+use crate::alarm;
+use crate::audio::Sound;
+use crate::zones;
+use crate::config::{
+    self, AlarmEntry, ClockEntry, Config, DstPolicy, GapPolicy, OverlapPolicy, Profile, Repeat,
+};
+use chrono::{NaiveTime, Utc};
+use chrono_tz::Tz;
+use clap::{ArgGroup, Args, Subcommand};
+use std::error::Error;
+
+type CommandResult = Result<(), Box<dyn Error>>;
+
+#[derive(Subcommand, Debug)]
+pub enum ClockCommand {
+    /// Add one or more clocks ("America/New_York" or "Label=America/New_York")
+    Add {
+        #[arg(required = true)]
+        zones: Vec<String>,
+        /// 1-based position to insert at (defaults to the end)
+        #[arg(long)]
+        at: Option<usize>,
+    },
+    /// Remove a clock by position, label or zone
+    Remove { clock: String },
+    /// List the saved clocks
+    List,
+    /// Move a clock to a new 1-based position
+    Move { clock: String, position: usize },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AlarmCommand {
+    /// Add one or more alarms as HH:MM (local time) or HH:MM@Zone, optionally
+    /// prefixed with "Label="
+    Add(Box<AddAlarm>),
+    /// Remove an alarm by position, #id, label or time (HH:MM or HH:MM@Zone)
+    Remove { alarm: String },
+    /// List the saved alarms
+    List,
+    /// Re-enable a disabled alarm
+    Enable { alarm: String },
+    /// Keep an alarm in the config but stop it from firing
+    Disable { alarm: String },
+}
+
+#[derive(Args, Debug)]
+pub struct AddAlarm {
+    #[arg(required_unless_present = "cron")]
+    times: Vec<String>,
+    /// Name shown when the alarm fires (e.g. "Standup")
+    #[arg(long)]
+    label: Option<String>,
+    /// Text shown alongside the label while the alarm rings
+    #[arg(long)]
+    message: Option<String>,
+    /// Sound file (WAV/OGG) the GUI plays for this alarm, or "off" for silence
+    #[arg(long, value_name = "FILE|off")]
+    sound: Option<String>,
+    /// Shell command to run when the alarm fires; it gets ALARM_LABEL,
+    /// ALARM_TIME and ALARM_ZONE in its environment
+    #[arg(long, value_name = "CMD")]
+    command: Option<String>,
+    /// If a DST change skips the alarm time: fire right after the gap, or not at all
+    #[arg(long, value_parser = ["next", "skip"], default_value = "next")]
+    on_gap: String,
+    /// If a DST change repeats the alarm time: fire the first time, the second, or both
+    #[arg(long, value_parser = ["first", "last", "both"], default_value = "first")]
+    on_overlap: String,
+    #[command(flatten)]
+    repeat: RepeatArgs,
+}
+
+/// How an added alarm repeats; without any of these it fires every day.
+#[derive(Args, Debug)]
+#[group(skip)]
+#[command(group = ArgGroup::new("schedule").multiple(false))]
+pub struct RepeatArgs {
+    /// Fire Monday to Friday only
+    #[arg(long, group = "schedule")]
+    weekdays: bool,
+    /// Fire on the given days only (e.g. "mon,wed,fri")
+    #[arg(long, group = "schedule", value_delimiter = ',')]
+    days: Vec<String>,
+    /// Fire once, on the given date (YYYY-MM-DD)
+    #[arg(long, group = "schedule", value_name = "DATE")]
+    on: Option<String>,
+    /// Fire every N minutes from the alarm time until --until
+    #[arg(long, group = "schedule", value_name = "N", requires = "until")]
+    every: Option<u32>,
+    /// End of the --every window (HH:MM)
+    #[arg(long, value_name = "HH:MM", requires = "every")]
+    until: Option<String>,
+    /// Fire on a five-field cron schedule instead of at a time of day
+    #[arg(long, group = "schedule", value_name = "EXPR", conflicts_with = "times")]
+    cron: Option<String>,
+    /// Zone a --cron schedule is evaluated in (defaults to local time)
+    #[arg(long, requires = "cron")]
+    zone: Option<String>,
+}
+
+impl RepeatArgs {
+    fn repeat(&self) -> Repeat {
+        if self.weekdays {
+            Repeat::Weekdays
+        } else if !self.days.is_empty() {
+            Repeat::Days { days: self.days.clone() }
+        } else if let Some(date) = &self.on {
+            Repeat::Once { date: date.clone() }
+        } else if let (Some(minutes), Some(until)) = (self.every, &self.until) {
+            Repeat::Every { minutes, until: until.clone() }
+        } else if let Some(expr) = &self.cron {
+            Repeat::Cron { expr: expr.clone() }
+        } else {
+            Repeat::Daily
+        }
+    }
+}
+
+pub fn clock(cmd: ClockCommand, profile: Option<String>) -> CommandResult {
+    match cmd {
+        ClockCommand::List => {
+            let config = config::load_config()?;
+            let (name, profile) = saved_profile(&config, profile)?;
+            if profile.clocks.is_empty() {
+                println!("No clocks saved in profile '{}'.", name);
+            }
+            for (i, entry) in profile.clocks.iter().enumerate() {
+                let now = entry
+                    .zone
+                    .parse::<Tz>()
+                    .map(|tz| Utc::now().with_timezone(&tz).format("%H:%M").to_string())
+                    .unwrap_or_else(|_| "invalid zone".to_string());
+                let pinned = if entry.pinned { " (pinned)" } else { "" };
+                match &entry.label {
+                    Some(label) => println!("{:>3}. {:<20} {:<30} {}{}", i + 1, label, entry.zone, now, pinned),
+                    None => println!("{:>3}. {:<20} {:<30} {}{}", i + 1, entry.zone, "", now, pinned),
+                }
+            }
+            Ok(())
+        }
+        ClockCommand::Add { zones, at } => {
+            let entries = zones
+                .iter()
+                .map(|zone| crate::parse_clock_arg(zone))
+                .collect::<Result<Vec<_>, _>>()?;
+            edit_profile(profile, |p| {
+                let index = at.map_or(p.clocks.len(), |at| at.saturating_sub(1).min(p.clocks.len()));
+                for (offset, entry) in entries.into_iter().enumerate() {
+                    println!("Added {}", describe_clock(&entry));
+                    p.clocks.insert(index + offset, entry);
+                }
+                Ok(())
+            })
+        }
+        ClockCommand::Remove { clock } => edit_profile(profile, |p| {
+            let index = find_clock(&p.clocks, &clock)?;
+            let removed = p.clocks.remove(index);
+            println!("Removed {}", describe_clock(&removed));
+            Ok(())
+        }),
+        ClockCommand::Move { clock, position } => edit_profile(profile, |p| {
+            let index = find_clock(&p.clocks, &clock)?;
+            let entry = p.clocks.remove(index);
+            let target = position.saturating_sub(1).min(p.clocks.len());
+            println!("Moved {} to position {}", describe_clock(&entry), target + 1);
+            p.clocks.insert(target, entry);
+            Ok(())
+        }),
+    }
+}
+
+pub fn alarm(cmd: AlarmCommand, profile: Option<String>) -> CommandResult {
+    match cmd {
+        AlarmCommand::List => {
+            let config = config::load_config()?;
+            let (name, profile) = saved_profile(&config, profile)?;
+            if profile.alarms.is_empty() {
+                println!("No alarms saved in profile '{}'.", name);
+            }
+            for (i, alarm) in profile.alarms.iter().enumerate() {
+                let state = if alarm.enabled { "" } else { " (disabled)" };
+                let id = format!("#{}", alarm.id);
+                let label = alarm.label.as_deref().unwrap_or("");
+                println!("{:>3}. {:<4} {:<20} {}{}", i + 1, id, label, describe_schedule(alarm), state);
+                if let Some(message) = &alarm.message {
+                    println!("{:>30}{}", "", message);
+                }
+                if let Some(command) = &alarm.command {
+                    println!("{:>30}runs: {}", "", command);
+                }
+            }
+            Ok(())
+        }
+        AlarmCommand::Add(add) => {
+            let AddAlarm { times, label, message, sound, command, on_gap, on_overlap, repeat } = *add;
+            if let Sound::File(path) = Sound::from_entry(sound.as_deref())
+                && !path.is_file()
+            {
+                return Err(format!("Sound file not found: {}", path.display()).into());
+            }
+            let dst = DstPolicy {
+                gap: if on_gap == "skip" { GapPolicy::Skip } else { GapPolicy::Next },
+                overlap: match on_overlap.as_str() {
+                    "last" => OverlapPolicy::Last,
+                    "both" => OverlapPolicy::Both,
+                    _ => OverlapPolicy::First,
+                },
+            };
+            let mut entries = times
+                .iter()
+                .map(|time| alarm::parse_alarm_arg(time))
+                .collect::<Result<Vec<_>, _>>()?;
+            if repeat.cron.is_some() {
+                let zone = match &repeat.zone {
+                    Some(zone) => Some(zones::resolve(zone)?.name().to_string()),
+                    None => None,
+                };
+                entries.push(AlarmEntry { zone, ..AlarmEntry::default() });
+            }
+            for entry in &mut entries {
+                entry.repeat = repeat.repeat();
+                if label.is_some() {
+                    entry.label = label.clone();
+                }
+                entry.message = message.clone();
+                entry.dst = dst;
+                entry.sound = sound.clone();
+                entry.command = command.clone();
+                alarm::Alarm::from_entry(entry)?;
+            }
+            edit_profile(profile, |p| {
+                let first = p.alarms.len();
+                p.alarms.extend(entries);
+                p.assign_alarm_ids();
+                for entry in &p.alarms[first..] {
+                    println!("Added alarm #{} {}", entry.id, describe_alarm(entry));
+                }
+                Ok(())
+            })
+        }
+        AlarmCommand::Remove { alarm } => edit_profile(profile, |p| {
+            let index = find_alarm(&p.alarms, &alarm)?;
+            let removed = p.alarms.remove(index);
+            println!("Removed alarm {}", describe_alarm(&removed));
+            Ok(())
+        }),
+        AlarmCommand::Enable { alarm } => set_enabled(profile, &alarm, true),
+        AlarmCommand::Disable { alarm } => set_enabled(profile, &alarm, false),
+    }
+}
+
+fn set_enabled(profile: Option<String>, alarm: &str, enabled: bool) -> CommandResult {
+    edit_profile(profile, |p| {
+        let index = find_alarm(&p.alarms, alarm)?;
+        p.alarms[index].enabled = enabled;
+        let state = if enabled { "Enabled" } else { "Disabled" };
+        println!("{} alarm {}", state, describe_alarm(&p.alarms[index]));
+        Ok(())
+    })
+}
+
+/// Applies `edit` to the selected profile (creating it if needed) and saves.
+fn edit_profile(
+    profile: Option<String>,
+    edit: impl FnOnce(&mut Profile) -> CommandResult,
+) -> CommandResult {
+    config::update_config(|c: &mut Config| {
+        let name = profile.unwrap_or_else(|| c.default_profile.clone());
+        edit(c.profile_mut(&name))
+    })?;
+    Ok(())
+}
+
+fn saved_profile(config: &Config, profile: Option<String>) -> Result<(String, Profile), Box<dyn Error>> {
+    let explicit = profile.is_some();
+    let name = profile.unwrap_or_else(|| config.default_profile.clone());
+    match config.profiles.get(&name) {
+        Some(profile) => Ok((name, profile.clone())),
+        None if explicit => Err(format!("Unknown profile: {}", name).into()),
+        None => Ok((name, Profile::default())),
+    }
+}
+
+/// Finds a clock by 1-based position, label or zone (case-insensitive).
+fn find_clock(clocks: &[ClockEntry], selector: &str) -> Result<usize, Box<dyn Error>> {
+    if let Some(index) = position(selector, clocks.len()) {
+        return Ok(index);
+    }
+    let matches: Vec<usize> = clocks
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| {
+            c.zone.eq_ignore_ascii_case(selector)
+                || c.label.as_deref().is_some_and(|l| l.eq_ignore_ascii_case(selector))
+        })
+        .map(|(i, _)| i)
+        .collect();
+    match matches.as_slice() {
+        [index] => Ok(*index),
+        [] => Err(format!("No clock matches '{}'", selector).into()),
+        _ => Err(format!("'{}' matches several clocks; use its position instead", selector).into()),
+    }
+}
+
+/// Finds an alarm by 1-based position, `#id`, label (case-insensitive) or
+/// `HH:MM[@Zone]`. A bare time matches the first alarm at that time in any zone.
+fn find_alarm(alarms: &[AlarmEntry], selector: &str) -> Result<usize, Box<dyn Error>> {
+    if let Some(index) = position(selector, alarms.len()) {
+        return Ok(index);
+    }
+    if let Some(id) = selector.strip_prefix('#') {
+        return alarms
+            .iter()
+            .position(|a| id.parse() == Ok(a.id))
+            .ok_or_else(|| format!("No alarm with id {}", selector).into());
+    }
+    let labelled: Vec<usize> = alarms
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| a.label.as_deref().is_some_and(|l| l.eq_ignore_ascii_case(selector)))
+        .map(|(i, _)| i)
+        .collect();
+    match labelled.as_slice() {
+        [index] => return Ok(*index),
+        [] => {}
+        _ => return Err(format!("'{}' matches several alarms; use its #id instead", selector).into()),
+    }
+    let wanted = alarm::parse_alarm_arg(selector)?;
+    alarms
+        .iter()
+        .position(|a| {
+            !matches!(a.repeat, Repeat::Cron { .. })
+                && NaiveTime::parse_from_str(&a.time, "%H:%M").ok()
+                    == NaiveTime::parse_from_str(&wanted.time, "%H:%M").ok()
+                && (wanted.zone.is_none() || a.zone == wanted.zone)
+        })
+        .ok_or_else(|| format!("No alarm at {}", selector).into())
+}
+
+fn position(selector: &str, len: usize) -> Option<usize> {
+    selector.parse::<usize>().ok().filter(|&n| n >= 1 && n <= len).map(|n| n - 1)
+}
+
+fn describe_clock(entry: &ClockEntry) -> String {
+    match &entry.label {
+        Some(label) => format!("{} ({})", label, entry.zone),
+        None => entry.zone.clone(),
+    }
+}
+
+/// "Standup (09:00 weekdays@Asia/Tokyo)", or just the schedule if unlabelled.
+pub fn describe_alarm(entry: &AlarmEntry) -> String {
+    match &entry.label {
+        Some(label) => format!("{} ({})", label, describe_schedule(entry)),
+        None => describe_schedule(entry),
+    }
+}
+
+/// "09:00 weekdays@Asia/Tokyo", falling back to the raw entry if it no
+/// longer parses.
+fn describe_schedule(entry: &AlarmEntry) -> String {
+    let summary = alarm::Alarm::from_entry(entry).map_or_else(|_| entry.time.clone(), |a| a.summary());
+    let mut text = match &entry.zone {
+        Some(zone) => format!("{}@{}", summary, zone),
+        None => summary,
+    };
+    if entry.dst.gap != GapPolicy::Next {
+        text.push_str(" [gap: skip]");
+    }
+    match entry.dst.overlap {
+        OverlapPolicy::First => {}
+        OverlapPolicy::Last => text.push_str(" [overlap: last]"),
+        OverlapPolicy::Both => text.push_str(" [overlap: both]"),
+    }
+    text
+}
@@ -0,0 +1,781 @@
+/*
+ * Configuration persistence.
+ *
+ * All user state lives in a single versioned document (`config.toml`, or
+ * `config.json` if the user prefers JSON). Older releases wrote two bare
+ * arrays to `clocks.json` and `alarms.json`; those are migrated on first run.
+ *
+ * Several instances (e.g. the TUI in one pane and `--gui` in another) may
+ * share the directory, so every read-modify-write happens under an advisory
+ * lock on `.lock`, and files are replaced by renaming a fully written temp
+ * file over them rather than truncating in place.
+ *
+ * Runtime state that is not configuration (when alarms were last checked)
+ * lives separately in `state.json`, so it can be updated every minute
+ * without rewriting the user's file.
+ */
+
+use chrono::{DateTime, NaiveTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Current schema version written to `Config::version`.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Profile used when none is named on the command line or in the config.
+pub const DEFAULT_PROFILE: &str = "default";
+
+const TOML_FILE: &str = "config.toml";
+const JSON_FILE: &str = "config.json";
+const LEGACY_CLOCKS_FILE: &str = "clocks.json";
+const LEGACY_ALARMS_FILE: &str = "alarms.json";
+const LOCK_FILE: &str = ".lock";
+const STATE_FILE: &str = "state.json";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The platform has no notion of a per-user config directory.
+    NoConfigDir,
+    Io { path: PathBuf, source: io::Error },
+    /// A config file exists but could not be parsed. `backup` is where the
+    /// unreadable file was moved so the next save cannot overwrite it.
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+        backup: Option<PathBuf>,
+    },
+    Serialize { path: PathBuf, message: String },
+    /// The config was written by a newer version with a schema this one does
+    /// not know; saving over it would lose whatever that version added.
+    TooNew { path: PathBuf, version: u32 },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NoConfigDir => write!(f, "could not determine a configuration directory"),
+            ConfigError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ConfigError::Parse { path, line, column, message, backup } => {
+                write!(f, "{}:{}:{}: {}", path.display(), line, column, message)?;
+                if let Some(backup) = backup {
+                    write!(f, " (the file was moved to {})", backup.display())?;
+                }
+                Ok(())
+            }
+            ConfigError::Serialize { path, message } => {
+                write!(f, "{}: could not serialize config: {}", path.display(), message)
+            }
+            ConfigError::TooNew { path, version } => write!(
+                f,
+                "{}: config version {} is newer than this program supports ({}); upgrade rust-world-clock to use it",
+                path.display(),
+                version,
+                CONFIG_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> ConfigError + '_ {
+    move |source| ConfigError::Io { path: path.to_path_buf(), source }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Config {
+    pub version: u32,
+    /// Profile loaded when `--profile` is not given.
+    pub default_profile: String,
+    pub display: DisplayPrefs,
+    pub alarm: AlarmPrefs,
+    pub keybindings: Keybindings,
+    pub profiles: BTreeMap<String, Profile>,
+
+    // Version 1 kept a single set of clocks and alarms at the top level;
+    // `upgrade` moves them into the default profile.
+    #[serde(rename = "clocks", skip_serializing)]
+    v1_clocks: Vec<ClockEntry>,
+    #[serde(rename = "alarms", skip_serializing)]
+    v1_alarms: Vec<AlarmEntry>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: CONFIG_VERSION,
+            default_profile: DEFAULT_PROFILE.to_string(),
+            display: DisplayPrefs::default(),
+            alarm: AlarmPrefs::default(),
+            keybindings: Keybindings::default(),
+            profiles: BTreeMap::new(),
+            v1_clocks: Vec::new(),
+            v1_alarms: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Returns the named profile, creating an empty one if it does not exist.
+    pub fn profile_mut(&mut self, name: &str) -> &mut Profile {
+        self.profiles.entry(name.to_string()).or_default()
+    }
+
+    fn assign_alarm_ids(&mut self) {
+        for profile in self.profiles.values_mut() {
+            profile.assign_alarm_ids();
+        }
+    }
+}
+
+/// A named set of clocks and alarms, e.g. "oncall" or "travel".
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Profile {
+    pub clocks: Vec<ClockEntry>,
+    pub alarms: Vec<AlarmEntry>,
+}
+
+impl Profile {
+    /// Gives alarms without an id (or sharing one with an earlier alarm) the
+    /// next unused id, leaving existing ids alone.
+    pub fn assign_alarm_ids(&mut self) {
+        let mut next = self.alarms.iter().map(|a| a.id).max().unwrap_or(0) + 1;
+        let mut seen = HashSet::new();
+        for alarm in &mut self.alarms {
+            if alarm.id == 0 || !seen.insert(alarm.id) {
+                alarm.id = next;
+                next += 1;
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ClockEntry {
+    /// IANA time zone identifier, e.g. "America/New_York".
+    pub zone: String,
+    /// Display name shown instead of the zone, e.g. "SF Office".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Pinned clocks are shown before all others.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AlarmEntry {
+    /// Number identifying the alarm within its profile. Zero means "not yet
+    /// assigned"; `Profile::assign_alarm_ids` fills those in.
+    #[serde(default)]
+    pub id: u32,
+    /// Name shown when the alarm fires, e.g. "Standup".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Longer text shown alongside the label while the alarm rings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// WAV/OGG file the GUI plays instead of its chime, or "off" for a
+    /// silent alarm. Unset means the bell in the TUI and the chime in the GUI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound: Option<String>,
+    /// Shell command run when the alarm fires, with the alarm's details in
+    /// `ALARM_*` environment variables.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Time of day in HH:MM format, in `zone` or else local time. Unused
+    /// (and usually empty) for cron schedules.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub time: String,
+    /// IANA zone the alarm is evaluated in, e.g. "Asia/Tokyo".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+    /// Disabled alarms are kept in the config but never fire.
+    #[serde(default = "enabled_default", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Repeat::is_daily")]
+    pub repeat: Repeat,
+    /// What to do when `time` is skipped or repeated by a DST change.
+    #[serde(default, skip_serializing_if = "DstPolicy::is_default")]
+    pub dst: DstPolicy,
+}
+
+/// When an alarm fires, e.g. `repeat = { kind = "days", days = ["mon", "thu"] }`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Repeat {
+    /// Every day at `time`.
+    #[default]
+    Daily,
+    /// Monday to Friday at `time`.
+    Weekdays,
+    /// The listed days ("mon", "Tuesday", ...) at `time`.
+    Days { days: Vec<String> },
+    /// Only on `date` (YYYY-MM-DD) at `time`.
+    Once { date: String },
+    /// Every `minutes` from `time` until `until` (HH:MM), each day.
+    Every { minutes: u32, until: String },
+    /// A five-field cron expression; `time` is ignored.
+    Cron { expr: String },
+}
+
+impl Default for AlarmEntry {
+    fn default() -> Self {
+        AlarmEntry {
+            id: 0,
+            label: None,
+            message: None,
+            sound: None,
+            command: None,
+            time: String::new(),
+            zone: None,
+            enabled: true,
+            repeat: Repeat::Daily,
+            dst: DstPolicy::default(),
+        }
+    }
+}
+
+impl Repeat {
+    fn is_daily(&self) -> bool {
+        *self == Repeat::Daily
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct DstPolicy {
+    pub gap: GapPolicy,
+    pub overlap: OverlapPolicy,
+}
+
+impl DstPolicy {
+    fn is_default(&self) -> bool {
+        *self == DstPolicy::default()
+    }
+}
+
+/// For alarm times that do not exist on a spring-forward day.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GapPolicy {
+    /// Fire at the first valid instant after the gap.
+    #[default]
+    Next,
+    /// Do not fire that day.
+    Skip,
+}
+
+/// For alarm times that happen twice on a fall-back day.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlapPolicy {
+    /// Fire on the first occurrence only.
+    #[default]
+    First,
+    /// Fire on the second occurrence only.
+    Last,
+    /// Fire on both.
+    Both,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DisplayPrefs {
+    /// strftime-style format for the main time line.
+    pub time_format: String,
+    /// strftime-style format for the date line.
+    pub date_format: String,
+    /// How the GUI draws its clocks.
+    pub face: ClockFace,
+    /// Draw the time in large block digits in terminal tiles big enough
+    /// for them.
+    pub big_digits: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockFace {
+    /// The time as text, in `time_format`.
+    #[default]
+    Digital,
+    /// A dial with hour, minute and second hands.
+    Analog,
+}
+
+impl Default for DisplayPrefs {
+    fn default() -> Self {
+        DisplayPrefs {
+            time_format: "%H:%M:%S".to_string(),
+            date_format: "%Y-%m-%d".to_string(),
+            face: ClockFace::Digital,
+            big_digits: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AlarmPrefs {
+    /// How long the `snooze` key puts a ringing alarm off for.
+    pub snooze_minutes: u32,
+    /// Ring alarms that went off while the app was asleep or not running as
+    /// soon as they are noticed, instead of only reporting them.
+    pub fire_missed: bool,
+    /// Also announce alarms as desktop notifications (freedesktop D-Bus).
+    pub notify: bool,
+    /// How long an alarm's command may run before it is killed.
+    pub command_timeout_seconds: u64,
+}
+
+impl Default for AlarmPrefs {
+    fn default() -> Self {
+        AlarmPrefs { snooze_minutes: 5, fire_missed: false, notify: false, command_timeout_seconds: 30 }
+    }
+}
+
+/// Key names accepted here are single characters ("q") or the named keys
+/// understood by the TUI ("space", "enter", "esc", "tab", "backtab", "up", ...).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Keybindings {
+    pub quit: Vec<String>,
+    pub dismiss: Vec<String>,
+    /// Snooze ringing alarms for `alarm.snooze_minutes`, or a fixed 5/10/15.
+    pub snooze: Vec<String>,
+    pub snooze_5: Vec<String>,
+    pub snooze_10: Vec<String>,
+    pub snooze_15: Vec<String>,
+    pub next_profile: Vec<String>,
+    pub add_clock: Vec<String>,
+    pub remove_clock: Vec<String>,
+    /// Swap the focused tile with the one before/after it.
+    pub move_earlier: Vec<String>,
+    pub move_later: Vec<String>,
+    pub focus_next: Vec<String>,
+    pub focus_prev: Vec<String>,
+    /// Move focus around the tile grid.
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+    pub up: Vec<String>,
+    pub down: Vec<String>,
+    pub details: Vec<String>,
+    pub rename: Vec<String>,
+    pub pin: Vec<String>,
+}
+
+impl Keybindings {
+    /// The fixed-length snooze bindings, with the minutes each snoozes for.
+    pub fn fixed_snoozes(&self) -> [(&[String], u32); 3] {
+        [(&self.snooze_5, 5), (&self.snooze_10, 10), (&self.snooze_15, 15)]
+    }
+
+    /// How long a key snoozes for, given whether it is `bound` to a list of
+    /// key names: the snooze key uses `snooze_minutes`, the others their
+    /// fixed length.
+    pub fn snooze_for(&self, snooze_minutes: u32, bound: impl Fn(&[String]) -> bool) -> Option<u32> {
+        std::iter::once((self.snooze.as_slice(), snooze_minutes))
+            .chain(self.fixed_snoozes())
+            .find(|(names, _)| bound(names))
+            .map(|(_, minutes)| minutes)
+    }
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Keybindings {
+            quit: vec!["q".to_string()],
+            dismiss: vec!["space".to_string(), "d".to_string()],
+            snooze: vec!["s".to_string()],
+            snooze_5: vec!["1".to_string()],
+            snooze_10: vec!["2".to_string()],
+            snooze_15: vec!["3".to_string()],
+            next_profile: vec!["p".to_string()],
+            add_clock: vec!["a".to_string()],
+            remove_clock: vec!["x".to_string(), "delete".to_string()],
+            move_earlier: vec!["<".to_string()],
+            move_later: vec![">".to_string()],
+            focus_next: vec!["tab".to_string()],
+            focus_prev: vec!["backtab".to_string()],
+            left: vec!["left".to_string(), "h".to_string()],
+            right: vec!["right".to_string(), "l".to_string()],
+            up: vec!["up".to_string(), "k".to_string()],
+            down: vec!["down".to_string(), "j".to_string()],
+            details: vec!["enter".to_string(), "i".to_string()],
+            rename: vec!["r".to_string()],
+            pin: vec!["P".to_string()],
+        }
+    }
+}
+
+// Formats written by releases before the unified config.
+#[derive(Deserialize)]
+struct LegacyClocks(Vec<String>);
+
+#[derive(Deserialize)]
+struct LegacyAlarms(Vec<String>);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Format {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Format::Json,
+            _ => Format::Toml,
+        }
+    }
+}
+
+pub fn get_config_dir() -> Result<PathBuf, ConfigError> {
+    let proj_dirs = ProjectDirs::from("com", "rust_world_clock", "rust_world_clock")
+        .ok_or(ConfigError::NoConfigDir)?;
+    let config_dir = proj_dirs.config_dir();
+    if !config_dir.exists() {
+        fs::create_dir_all(config_dir).map_err(io_error(config_dir))?;
+    }
+    Ok(config_dir.to_path_buf())
+}
+
+/// Returns the config file in use: an existing `config.json` wins, otherwise
+/// `config.toml` (which may not exist yet).
+fn config_path(config_dir: &Path) -> PathBuf {
+    let json = config_dir.join(JSON_FILE);
+    if json.exists() {
+        json
+    } else {
+        config_dir.join(TOML_FILE)
+    }
+}
+
+/// Reads a file, treating "not found" as `None` rather than an error.
+fn read_optional(path: &Path) -> Result<Option<String>, ConfigError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(io_error(path)(e)),
+    }
+}
+
+/// Deserializes `content` read from `path`. On failure the file is moved to
+/// `<name>.bak` and the error carries the 1-based line and column.
+fn parse<T: for<'de> Deserialize<'de>>(path: &Path, content: &str) -> Result<T, ConfigError> {
+    let result = match Format::from_path(path) {
+        Format::Toml => toml::from_str(content).map_err(|e| {
+            let offset = e.span().map(|span| span.start).unwrap_or(0);
+            let (line, column) = line_column(content, offset);
+            (line, column, e.message().to_string())
+        }),
+        Format::Json => serde_json::from_str(content).map_err(|e| {
+            // serde_json appends the position to its message; we report it separately.
+            let suffix = format!(" at line {} column {}", e.line(), e.column());
+            let message = e.to_string();
+            let message = message.strip_suffix(&suffix).unwrap_or(&message).to_string();
+            (e.line(), e.column(), message)
+        }),
+    };
+
+    result.map_err(|(line, column, message)| {
+        let backup = backup_path(path);
+        let backup = fs::rename(path, &backup).ok().map(|_| backup);
+        ConfigError::Parse { path: path.to_path_buf(), line, column, message, backup }
+    })
+}
+
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+fn serialize(config: &Config, path: &Path) -> Result<String, ConfigError> {
+    let result = match Format::from_path(path) {
+        Format::Toml => toml::to_string_pretty(config).map_err(|e| e.to_string()),
+        Format::Json => serde_json::to_string_pretty(config).map_err(|e| e.to_string()),
+    };
+    result.map_err(|message| ConfigError::Serialize { path: path.to_path_buf(), message })
+}
+
+/// Exclusive advisory lock on the config directory, released on drop.
+struct DirLock {
+    _file: File,
+}
+
+fn lock_dir(config_dir: &Path) -> Result<DirLock, ConfigError> {
+    let path = config_dir.join(LOCK_FILE);
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(io_error(&path))?;
+    file.lock().map_err(io_error(&path))?;
+    Ok(DirLock { _file: file })
+}
+
+/// Writes `content` to a sibling temp file, flushes it to disk and renames it
+/// over `path`, so readers see either the old or the new file, never a mix.
+fn write_atomic(path: &Path, content: &str) -> Result<(), ConfigError> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".tmp.{}", std::process::id()));
+    let tmp = path.with_file_name(tmp_name);
+
+    let result = File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(io_error(path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result?;
+
+    // Persist the rename itself; not possible (or needed) on every platform.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent()
+        && let Ok(dir) = File::open(dir)
+    {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct State {
+    /// RFC 3339 time at which each profile's alarms were last evaluated.
+    /// Profiles are tracked separately because only the running one's alarms
+    /// are watched.
+    last_evaluated: BTreeMap<String, String>,
+}
+
+/// State is best-effort, so a missing or unreadable file just means "unknown".
+fn read_state(path: &Path) -> State {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// When a previous run of `profile` last evaluated its alarms.
+pub fn load_last_evaluated(profile: &str) -> Option<DateTime<Utc>> {
+    let state = read_state(&get_config_dir().ok()?.join(STATE_FILE));
+    let at = DateTime::parse_from_rfc3339(state.last_evaluated.get(profile)?).ok()?;
+    Some(at.with_timezone(&Utc))
+}
+
+pub fn save_last_evaluated(profile: &str, at: DateTime<Utc>) -> Result<(), ConfigError> {
+    let config_dir = get_config_dir()?;
+    // Another instance may be saving for a different profile.
+    let _lock = lock_dir(&config_dir)?;
+    let path = config_dir.join(STATE_FILE);
+    let mut state = read_state(&path);
+    state.last_evaluated.insert(profile.to_string(), at.to_rfc3339());
+    let content = serde_json::to_string_pretty(&state)
+        .map_err(|e| ConfigError::Serialize { path: path.clone(), message: e.to_string() })?;
+    write_atomic(&path, &content)
+}
+
+pub fn load_config() -> Result<Config, ConfigError> {
+    let config_dir = get_config_dir()?;
+    let _lock = lock_dir(&config_dir)?;
+    read_config(&config_dir)
+}
+
+/// Re-reads the config under the directory lock, applies `change` and writes
+/// it back, so edits from another running instance are not lost. Nothing is
+/// written if `change` fails.
+pub fn update_config<E: From<ConfigError>>(
+    change: impl FnOnce(&mut Config) -> Result<(), E>,
+) -> Result<Config, E> {
+    let config_dir = get_config_dir()?;
+    let _lock = lock_dir(&config_dir)?;
+    let mut config = read_config(&config_dir)?;
+    change(&mut config)?;
+    config.assign_alarm_ids();
+    write_config(&config_dir, &config)?;
+    Ok(config)
+}
+
+// The helpers below expect the caller to hold the directory lock.
+
+fn read_config(config_dir: &Path) -> Result<Config, ConfigError> {
+    let path = config_path(config_dir);
+    if let Some(content) = read_optional(&path)? {
+        return parse(&path, &content).and_then(|config| upgrade(config, &path));
+    }
+
+    match migrate_legacy(config_dir)? {
+        Some(config) => {
+            write_config(config_dir, &config)?;
+            retire_legacy(config_dir)?;
+            Ok(config)
+        }
+        None => Ok(Config::default()),
+    }
+}
+
+fn write_config(config_dir: &Path, config: &Config) -> Result<(), ConfigError> {
+    let path = config_path(config_dir);
+    let content = serialize(config, &path)?;
+    write_atomic(&path, &content)
+}
+
+/// Brings a config written by an older schema up to `CONFIG_VERSION`, and
+/// refuses one written by a newer schema.
+fn upgrade(mut config: Config, path: &Path) -> Result<Config, ConfigError> {
+    if config.version > CONFIG_VERSION {
+        return Err(ConfigError::TooNew { path: path.to_path_buf(), version: config.version });
+    }
+    if config.version < 2 || !config.v1_clocks.is_empty() || !config.v1_alarms.is_empty() {
+        let clocks = std::mem::take(&mut config.v1_clocks);
+        let alarms = std::mem::take(&mut config.v1_alarms);
+        let profile = config.profile_mut(DEFAULT_PROFILE);
+        profile.clocks = clocks;
+        profile.alarms = alarms;
+    }
+    config.version = CONFIG_VERSION;
+    config.assign_alarm_ids();
+    Ok(config)
+}
+
+/// Builds a config from the pre-unified `clocks.json`/`alarms.json`, if any.
+fn migrate_legacy(config_dir: &Path) -> Result<Option<Config>, ConfigError> {
+    let clocks_path = config_dir.join(LEGACY_CLOCKS_FILE);
+    let clocks = match read_optional(&clocks_path)? {
+        Some(content) => Some(parse::<LegacyClocks>(&clocks_path, &content)?),
+        None => None,
+    };
+    let alarms_path = config_dir.join(LEGACY_ALARMS_FILE);
+    let alarms = match read_optional(&alarms_path)? {
+        Some(content) => Some(parse::<LegacyAlarms>(&alarms_path, &content)?),
+        None => None,
+    };
+
+    if clocks.is_none() && alarms.is_none() {
+        return Ok(None);
+    }
+
+    let mut config = Config::default();
+    let profile = config.profile_mut(DEFAULT_PROFILE);
+    profile.clocks = clocks
+        .map(|c| c.0.into_iter().map(|zone| ClockEntry { zone, label: None, pinned: false }).collect())
+        .unwrap_or_default();
+    // Old releases skipped entries they could not parse; keep those, but
+    // disabled, so one bad line does not stop every start-up.
+    profile.alarms = alarms
+        .map(|a| {
+            a.0.into_iter()
+                .map(|time| {
+                    let enabled = NaiveTime::parse_from_str(&time, "%H:%M").is_ok();
+                    AlarmEntry { time, enabled, ..AlarmEntry::default() }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    config.assign_alarm_ids();
+    Ok(Some(config))
+}
+
+/// Renames the legacy files so they are not picked up again.
+fn retire_legacy(config_dir: &Path) -> Result<(), ConfigError> {
+    for name in [LEGACY_CLOCKS_FILE, LEGACY_ALARMS_FILE] {
+        let path = config_dir.join(name);
+        if path.exists() {
+            fs::rename(&path, config_dir.join(format!("{}.migrated", name))).map_err(io_error(&path))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upgraded(content: &str) -> Result<Config, ConfigError> {
+        upgrade(toml::from_str(content).unwrap(), Path::new("config.toml"))
+    }
+
+    #[test]
+    fn snooze_keys_map_to_their_lengths() {
+        let keys = Keybindings::default();
+        let pressed = |key: &str| keys.snooze_for(7, |names| names.iter().any(|name| name == key));
+        assert_eq!(pressed("s"), Some(7));
+        assert_eq!(pressed("1"), Some(5));
+        assert_eq!(pressed("3"), Some(15));
+        assert_eq!(pressed("q"), None);
+    }
+
+    #[test]
+    fn version_1_moves_into_the_default_profile() {
+        let config = upgraded("version = 1\nclocks = [{ zone = \"Asia/Tokyo\" }]\nalarms = [{ time = \"09:00\" }]\n").unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        let profile = &config.profiles[DEFAULT_PROFILE];
+        assert_eq!(profile.clocks[0].zone, "Asia/Tokyo");
+        assert_eq!(profile.alarms[0].id, 1);
+    }
+
+    #[test]
+    fn legacy_files_are_migrated_with_unparsable_alarms_disabled() {
+        let dir = std::env::temp_dir().join(format!("rust-world-clock-migrate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(LEGACY_CLOCKS_FILE), r#"["Asia/Tokyo", "Europe/London"]"#).unwrap();
+        fs::write(dir.join(LEGACY_ALARMS_FILE), r#"["07:30", "25:00"]"#).unwrap();
+
+        let config = migrate_legacy(&dir).unwrap().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let profile = &config.profiles[DEFAULT_PROFILE];
+        let zones: Vec<&str> = profile.clocks.iter().map(|clock| clock.zone.as_str()).collect();
+        assert_eq!(zones, ["Asia/Tokyo", "Europe/London"]);
+        let alarms: Vec<(&str, bool)> = profile.alarms.iter().map(|alarm| (alarm.time.as_str(), alarm.enabled)).collect();
+        assert_eq!(alarms, [("07:30", true), ("25:00", false)]);
+        assert_eq!(profile.alarms.iter().map(|alarm| alarm.id).collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let newer = format!("version = {}\n", CONFIG_VERSION + 1);
+        assert!(matches!(upgraded(&newer), Err(ConfigError::TooNew { version, .. }) if version == CONFIG_VERSION + 1));
+    }
+}
@@ -0,0 +1,187 @@
+/*
+ * Minimal five-field cron expressions ("minute hour day-of-month month
+ * day-of-week") for alarm schedules. Supports `*`, lists, ranges, steps,
+ * month/day names and the @hourly/@daily/@weekly/@monthly/@yearly macros.
+ */
+
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CronExpr {
+    source: String,
+    minutes: u64,
+    hours: u32,
+    days_of_month: u32,
+    months: u16,
+    days_of_week: u8,
+    /// Whether the day fields started with `*` (as in `*` or `*/2`); cron ORs
+    /// the two day fields when both are restricted and otherwise uses
+    /// whichever one is.
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+impl CronExpr {
+    pub fn parse(source: &str) -> Result<CronExpr, String> {
+        let expanded = match source.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields.as_slice() else {
+            return Err(format!("Invalid cron expression '{}': expected 5 fields", source));
+        };
+        let invalid = |e: String| format!("Invalid cron expression '{}': {}", source, e);
+
+        // Day of week accepts 0-7, with both 0 and 7 meaning Sunday.
+        let dow_bits = parse_field(dow, 0, 7, &WEEKDAYS).map_err(invalid)?;
+        let dow_bits = (dow_bits | (dow_bits >> 7)) & 0x7f;
+
+        Ok(CronExpr {
+            source: source.trim().to_string(),
+            minutes: parse_field(minute, 0, 59, &[]).map_err(invalid)?,
+            hours: parse_field(hour, 0, 23, &[]).map_err(invalid)? as u32,
+            days_of_month: parse_field(dom, 1, 31, &[]).map_err(invalid)? as u32,
+            months: parse_field(month, 1, 12, &MONTHS).map_err(invalid)? as u16,
+            days_of_week: dow_bits as u8,
+            any_day_of_month: dom.starts_with('*'),
+            any_day_of_week: dow.starts_with('*'),
+        })
+    }
+
+    pub fn matches(&self, at: NaiveDateTime) -> bool {
+        let dom = self.days_of_month & (1 << at.day()) != 0;
+        let dow = self.days_of_week & (1 << at.weekday().num_days_from_sunday()) != 0;
+        let day = match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => dom || dow,
+            _ => dom && dow,
+        };
+        self.minutes & (1 << at.minute()) != 0
+            && self.hours & (1 << at.hour()) != 0
+            && self.months & (1 << at.month()) != 0
+            && day
+    }
+}
+
+impl fmt::Display for CronExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Parses one field into a bitmask where bit `n` is set if value `n` matches.
+/// `names[i]` is accepted in place of the number `min + i`.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let lower = s.to_ascii_lowercase();
+        if let Some(i) = names.iter().position(|name| *name == lower) {
+            return Ok(min + i as u32);
+        }
+        match s.parse::<u32>() {
+            Ok(n) if (min..=max).contains(&n) => Ok(n),
+            _ => Err(format!("'{}' is not in {}-{}", s, min, max)),
+        }
+    };
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, Some(step)),
+                _ => return Err(format!("invalid step '{}'", step)),
+            },
+            None => (part, None),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (value(a)?, value(b)?),
+                // "5/15" means "from 5 to the end, every 15".
+                None if step.is_some() => (value(range)?, max),
+                None => {
+                    let n = value(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("range '{}' is backwards", range));
+        }
+        for n in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    // 2026-10-15 is a Thursday.
+    #[test]
+    fn matches() {
+        let cases = [
+            // Lists and ranges.
+            ("0,30 9 * * *", "2026-10-15 09:30", true),
+            ("0,30 9 * * *", "2026-10-15 09:15", false),
+            ("0 9-17 * * *", "2026-10-15 17:00", true),
+            ("0 9-17 * * *", "2026-10-15 18:00", false),
+            // Steps, over `*`, a range, or from a start value to the end.
+            ("*/15 * * * *", "2026-10-15 10:45", true),
+            ("*/15 * * * *", "2026-10-15 10:50", false),
+            ("10-20/5 * * * *", "2026-10-15 10:15", true),
+            ("10-20/5 * * * *", "2026-10-15 10:25", false),
+            ("5/20 * * * *", "2026-10-15 10:45", true),
+            ("5/1 * * * *", "2026-10-15 10:06", true),
+            ("5/1 * * * *", "2026-10-15 10:04", false),
+            // Month and day names, in any case.
+            ("0 9 * oct *", "2026-10-15 09:00", true),
+            ("0 9 * Jan-Mar *", "2026-10-15 09:00", false),
+            ("0 9 * * THU", "2026-10-15 09:00", true),
+            ("0 9 * * mon-wed", "2026-10-15 09:00", false),
+            // Both 0 and 7 are Sunday.
+            ("0 9 * * 0", "2026-10-18 09:00", true),
+            ("0 9 * * 7", "2026-10-18 09:00", true),
+            ("0 9 * * 5-7", "2026-10-18 09:00", true),
+            ("0 9 * * 7", "2026-10-17 09:00", false),
+            // Two restricted day fields match on either.
+            ("0 9 1 * mon", "2026-10-01 09:00", true),
+            ("0 9 1 * mon", "2026-10-19 09:00", true),
+            ("0 9 1 * mon", "2026-10-15 09:00", false),
+            // A day field starting with `*` is not restricted, so both apply.
+            ("0 9 */2 * mon", "2026-10-19 09:00", true),
+            ("0 9 */2 * mon", "2026-10-26 09:00", false),
+            ("0 9 */2 * mon", "2026-10-15 09:00", false),
+            ("0 9 15 * *", "2026-10-15 09:00", true),
+            ("0 9 * * mon", "2026-10-15 09:00", false),
+            // Macros.
+            ("@weekly", "2026-10-18 00:00", true),
+            ("@weekly", "2026-10-15 00:00", false),
+            ("@monthly", "2026-10-01 00:00", true),
+        ];
+        for (expr, time, expected) in cases {
+            let cron = CronExpr::parse(expr).unwrap();
+            assert_eq!(cron.matches(at(time)), expected, "{} at {}", expr, time);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expr in ["* * * *", "60 * * * *", "* 24 * * *", "* * 0 * *", "*/0 * * * *", "5-1 * * * *", "* * * foo *"] {
+            assert!(CronExpr::parse(expr).is_err(), "{}", expr);
+        }
+    }
+}
@@ -1,42 +1,121 @@
+use crate::alarm;
+use crate::analog::AnalogFace;
+use crate::commands;
+use crate::config::{self, AlarmEntry, ClockEntry, ClockFace, Config, ConfigError, Profile};
+use crate::outputs::AlarmOutputs;
+use crate::scheduler::{AlarmEvent, Missed, Scheduler};
+use crate::zones::{self, Match};
 use crate::Clock;
-use chrono::{Local, NaiveTime, Timelike, Utc};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use iced::{
     executor,
-    widget::{container, column, row, text, Container},
+    keyboard::{self, key::Named, Key, Modifiers},
+    widget::{button, canvas, checkbox, container, column, row, scrollable, text, text_input},
+    window::{self, UserAttention},
     Application, Command, Element, Length, Settings, Subscription, Theme, Color, Alignment,
 };
 use std::time::{Duration};
 
-pub fn run(clocks: Vec<Clock>, alarms: Vec<NaiveTime>) -> iced::Result {
-    WorldClockApp::run(Settings {
-        flags: (clocks, alarms),
-        ..Settings::default()
-    })
+pub fn run(
+    clocks: Vec<Clock>,
+    scheduler: Scheduler,
+    outputs: AlarmOutputs,
+    config: Config,
+    session: Session,
+) -> iced::Result {
+    WorldClockApp::run(Settings::with_flags((clocks, scheduler, outputs, config, session)))
+}
+
+/// The profile the GUI edits, and whether its edits may be saved.
+pub struct Session {
+    pub profile: String,
+    /// The alarm entries in use, including disabled ones.
+    pub alarms: Vec<AlarmEntry>,
+    /// False when the clocks or alarms came from the command line without --save.
+    pub persist_clocks: bool,
+    pub persist_alarms: bool,
 }
 
+/// How many zone search results the clock picker lists.
+const PICKER_RESULTS: usize = 8;
+
 struct WorldClockApp {
     clocks: Vec<Clock>,
-    alarms: Vec<NaiveTime>,
-    local_time: NaiveTime,
+    scheduler: Scheduler,
+    outputs: AlarmOutputs,
+    /// Missed alarms reported by the scheduler.
+    missed: Vec<Missed>,
+    config: Config,
+    session: Session,
+    now: DateTime<Utc>,
+    show_alarms: bool,
+    /// Contents of the alarm panel's time and label fields.
+    new_time: String,
+    new_label: String,
+    /// Last edit or save error.
+    status: Option<String>,
+    /// Whether the cards show their edit buttons and the zone picker is up.
+    editing_clocks: bool,
+    zone_query: String,
+    zone_results: Vec<Match>,
+    /// Index of the card whose label is being edited, and the text so far.
+    renaming: Option<(usize, String)>,
 }
 
 #[derive(Debug, Clone)]
 enum Message {
-    Tick(NaiveTime),
+    Tick,
+    /// A key nobody else handled, by its keybinding name ("space", "s").
+    KeyPressed(String),
+    Dismiss,
+    /// Snooze everything ringing for this many minutes.
+    Snooze(u32),
+    ToggleAlarmPanel,
+    NewAlarmTime(String),
+    NewAlarmLabel(String),
+    AddAlarm,
+    RemoveAlarm(u32),
+    ToggleAlarm(u32),
+    ToggleClockEditing,
+    ToggleFace,
+    ZoneQuery(String),
+    /// Enter in the zone search adds the best match.
+    AddBestMatch,
+    AddClock(Tz),
+    RemoveClock(usize),
+    /// Move the card at the index this many places along.
+    MoveClock(usize, isize),
+    StartRename(usize),
+    RenameInput(String),
+    FinishRename,
 }
 
 impl Application for WorldClockApp {
     type Executor = executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = (Vec<Clock>, Vec<NaiveTime>);
+    type Flags = (Vec<Clock>, Scheduler, AlarmOutputs, Config, Session);
 
     fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        let (clocks, scheduler, outputs, config, session) = flags;
         (
             WorldClockApp {
-                clocks: flags.0,
-                alarms: flags.1,
-                local_time: Local::now().time(),
+                clocks,
+                now: scheduler.now(),
+                scheduler,
+                outputs,
+                missed: Vec::new(),
+                config,
+                session,
+                show_alarms: false,
+                new_time: String::new(),
+                new_label: String::new(),
+                status: None,
+                editing_clocks: false,
+                zone_query: String::new(),
+                zone_results: Vec::new(),
+                renaming: None,
             },
             Command::none(),
         )
@@ -48,29 +127,241 @@ impl Application for WorldClockApp {
 
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
-            Message::Tick(time) => {
-                self.local_time = time;
+            Message::Tick => {
+                let mut commands = Vec::new();
+                let events = self.scheduler.tick();
+                if let Some(failure) = self.outputs.handle(&mut self.scheduler, &events).pop() {
+                    self.status = Some(failure);
+                }
+                for event in events {
+                    match event {
+                        AlarmEvent::Fired(_) => {
+                            commands.push(window::request_user_attention(
+                                window::Id::MAIN,
+                                Some(UserAttention::Critical),
+                            ));
+                        }
+                        AlarmEvent::Missed(missed) => self.missed.push(missed),
+                        AlarmEvent::Evaluated(at) => {
+                            if let Err(e) = config::save_last_evaluated(&self.session.profile, at) {
+                                self.status = Some(e.to_string());
+                            }
+                        }
+                    }
+                }
+                self.now = self.scheduler.now();
+                Command::batch(commands)
+            }
+            Message::KeyPressed(name) => {
+                let keys = &self.config.keybindings;
+                let snooze = keys.snooze_for(self.config.alarm.snooze_minutes, |names| bound(names, &name));
+                if bound(&keys.dismiss, &name) {
+                    self.dismiss();
+                } else if let Some(minutes) = snooze {
+                    self.scheduler.snooze(minutes);
+                }
+                Command::none()
+            }
+            Message::Dismiss => {
+                self.dismiss();
+                Command::none()
+            }
+            Message::Snooze(minutes) => {
+                self.scheduler.snooze(minutes);
+                Command::none()
+            }
+            Message::ToggleAlarmPanel => {
+                self.show_alarms = !self.show_alarms;
+                Command::none()
+            }
+            Message::NewAlarmTime(time) => {
+                self.new_time = time;
+                Command::none()
+            }
+            Message::NewAlarmLabel(label) => {
+                self.new_label = label;
+                Command::none()
+            }
+            Message::AddAlarm => {
+                let mut entry = match alarm::parse_alarm_arg(self.new_time.trim()) {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        self.status = Some(e);
+                        return Command::none();
+                    }
+                };
+                let label = self.new_label.trim();
+                if !label.is_empty() {
+                    entry.label = Some(label.to_string());
+                }
+                if self.edit_alarms(|alarms| alarms.push(entry)) {
+                    self.new_time.clear();
+                    self.new_label.clear();
+                }
+                Command::none()
+            }
+            Message::RemoveAlarm(id) => {
+                self.edit_alarms(|alarms| alarms.retain(|alarm| alarm.id != id));
+                Command::none()
+            }
+            Message::ToggleAlarm(id) => {
+                self.edit_alarms(|alarms| {
+                    if let Some(alarm) = alarms.iter_mut().find(|alarm| alarm.id == id) {
+                        alarm.enabled = !alarm.enabled;
+                    }
+                });
+                Command::none()
+            }
+            Message::ToggleClockEditing => {
+                self.editing_clocks = !self.editing_clocks;
+                self.renaming = None;
+                Command::none()
+            }
+            Message::ToggleFace => {
+                let face = match self.config.display.face {
+                    ClockFace::Digital => ClockFace::Analog,
+                    ClockFace::Analog => ClockFace::Digital,
+                };
+                self.config.display.face = face;
+                // A display preference, so saved whatever the clocks came from.
+                match config::update_config(|c| {
+                    c.display.face = face;
+                    Ok::<_, ConfigError>(())
+                }) {
+                    Ok(config) => self.config = config,
+                    Err(e) => self.status = Some(format!("Save failed: {}", e)),
+                }
+                Command::none()
+            }
+            Message::ZoneQuery(query) => {
+                self.zone_results = if query.trim().is_empty() { Vec::new() } else { zones::search(&query, PICKER_RESULTS) };
+                self.zone_query = query;
+                Command::none()
+            }
+            Message::AddBestMatch => {
+                if let Some(tz) = self.zone_results.first().map(|m| m.tz) {
+                    self.add_clock(tz);
+                }
+                Command::none()
+            }
+            Message::AddClock(tz) => {
+                self.add_clock(tz);
+                Command::none()
+            }
+            Message::RemoveClock(index) => {
+                if index < self.clocks.len() {
+                    self.clocks.remove(index);
+                    self.renaming = None;
+                    self.save_clocks();
+                }
+                Command::none()
+            }
+            Message::MoveClock(index, delta) => {
+                // Cards do not cross between the pinned and unpinned groups.
+                let target = index as isize + delta;
+                if index < self.clocks.len()
+                    && target >= 0
+                    && (target as usize) < self.clocks.len()
+                    && self.clocks[target as usize].pinned == self.clocks[index].pinned
+                {
+                    self.clocks.swap(index, target as usize);
+                    self.renaming = None;
+                    self.save_clocks();
+                }
+                Command::none()
+            }
+            Message::StartRename(index) => {
+                if let Some(clock) = self.clocks.get(index) {
+                    self.renaming = Some((index, clock.name.clone()));
+                }
+                Command::none()
+            }
+            Message::RenameInput(name) => {
+                if let Some((_, text)) = &mut self.renaming {
+                    *text = name;
+                }
+                Command::none()
+            }
+            Message::FinishRename => {
+                // An empty label goes back to the zone name.
+                if let Some((index, name)) = self.renaming.take()
+                    && let Some(clock) = self.clocks.get_mut(index)
+                {
+                    let name = name.trim();
+                    clock.name = if name.is_empty() { clock.timezone.name().to_string() } else { name.to_string() };
+                    self.save_clocks();
+                }
+                Command::none()
             }
         }
-        Command::none()
     }
 
-    fn view(&self) -> Element<Message> {
-        let is_alarm_active = self.alarms.iter().any(|&alarm| {
-            self.local_time.hour() == alarm.hour() && self.local_time.minute() == alarm.minute()
-        });
+    fn view(&self) -> Element<'_, Message> {
+        let ringing = self.scheduler.ringing(self.now);
+        let is_alarm_active = !ringing.is_empty();
+
+        let clock_content = self.clocks.iter().enumerate().map(|(index, clock)| {
+            let time = self.now.with_timezone(&clock.timezone);
+            let time_str = time.format(&self.config.display.time_format).to_string();
+            let date_str = time.format(&self.config.display.date_format).to_string();
+
+            // While editing, the name is a button that turns into a text field.
+            let name_color = Color::from_rgb(1.0, 1.0, 0.0); // Yellow-ish
+            let name: Element<'_, Message> = match &self.renaming {
+                Some((renaming, value)) if *renaming == index => text_input(clock.timezone.name(), value)
+                    .on_input(Message::RenameInput)
+                    .on_submit(Message::FinishRename)
+                    .width(Length::Fixed(200.0))
+                    .into(),
+                _ if self.editing_clocks => button(text(&clock.name).size(20).style(name_color))
+                    .on_press(Message::StartRename(index))
+                    .style(iced::theme::Button::Text)
+                    .into(),
+                _ => text(&clock.name).size(20).style(name_color).into(),
+            };
+            let mut card = column![name];
+            if let Some(zone) = clock.subtitle() {
+                card = card.push(text(zone).size(12).style(Color::from_rgb(0.5, 0.5, 0.5)));
+            }
+            card = match self.config.display.face {
+                ClockFace::Digital => card.push(text(time_str).size(40).style(Color::from_rgb(0.0, 1.0, 1.0))), // Cyan-ish
+                ClockFace::Analog => card.push(
+                    canvas(AnalogFace { time: time.time() })
+                        .width(Length::Fixed(160.0))
+                        .height(Length::Fixed(160.0)),
+                ),
+            };
+            card = card.push(text(date_str).size(15).style(Color::from_rgb(0.5, 0.5, 0.5))); // Gray
 
-        let clock_content = self.clocks.iter().map(|clock| {
-            let time = Utc::now().with_timezone(&clock.timezone);
-            let time_str = time.format("%H:%M:%S").to_string();
-            let date_str = time.format("%Y-%m-%d").to_string();
+            // Alarms set in this card's zone
+            let ringing = self.scheduler.ringing(self.now);
+            for alarm in self.scheduler.alarms().iter().filter(|alarm| alarm.zone == Some(clock.timezone)) {
+                let name = alarm.label.as_deref().unwrap_or("Alarm");
+                let mut line = format!("{} {}", name, alarm.short());
+                let color = if ringing.iter().any(|r| r.id == alarm.id) {
+                    Color::from_rgb(1.0, 0.3, 0.3)
+                } else if let Some(until) = self.scheduler.snoozed_until(alarm, self.now) {
+                    line.push_str(&format!(" (snoozed to {})", until.with_timezone(&clock.timezone).format("%H:%M")));
+                    Color::from_rgb(1.0, 0.85, 0.2)
+                } else {
+                    Color::from_rgb(0.5, 0.5, 0.5)
+                };
+                card = card.push(text(line).size(13).style(color));
+            }
+
+            if self.editing_clocks {
+                card = card.push(
+                    row![
+                        button(text("<")).on_press(Message::MoveClock(index, -1)),
+                        button(text(">")).on_press(Message::MoveClock(index, 1)),
+                        button(text("Delete")).on_press(Message::RemoveClock(index)),
+                    ]
+                    .spacing(5),
+                );
+            }
 
             container(
-                column![
-                    text(&clock.name).size(20).style(Color::from_rgb(1.0, 1.0, 0.0)), // Yellow-ish
-                    text(time_str).size(40).style(Color::from_rgb(0.0, 1.0, 1.0)), // Cyan-ish
-                    text(date_str).size(15).style(Color::from_rgb(0.5, 0.5, 0.5)), // Gray
-                ]
+                card
                 .align_items(Alignment::Center)
                 .spacing(10)
             )
@@ -88,7 +379,66 @@ impl Application for WorldClockApp {
             .into()
         });
 
-        let content = row(clock_content).spacing(20).padding(20).align_items(Alignment::Center);
+        let clocks = row(clock_content).spacing(20).padding(20).align_items(Alignment::Center);
+
+        // Banner naming whatever is ringing, above the cards.
+        let mut content = column![].align_items(Alignment::Center).spacing(10);
+        for alarm in ringing {
+            let schedule = match alarm.zone {
+                Some(tz) => format!("{} {}", alarm.summary(), tz.name()),
+                None => alarm.summary(),
+            };
+            content = content
+                .push(text(alarm.title()).size(28).style(Color::from_rgb(1.0, 0.3, 0.3)))
+                .push(text(schedule).size(13).style(Color::from_rgb(0.5, 0.5, 0.5)));
+            if let Some(message) = &alarm.message {
+                content = content.push(text(message).size(16));
+            }
+        }
+        for missed in &self.missed {
+            let line = format!("Missed: {}", missed.describe(self.now));
+            content = content.push(text(line).size(14).style(Color::from_rgb(0.9, 0.4, 0.9)));
+        }
+        if is_alarm_active || !self.missed.is_empty() {
+            let mut actions = row![button(text("Dismiss")).on_press(Message::Dismiss)].spacing(10);
+            if is_alarm_active {
+                let minutes = self.config.alarm.snooze_minutes;
+                let snooze = format!("Snooze {}m", minutes);
+                actions = actions.push(button(text(snooze)).on_press(Message::Snooze(minutes)));
+                // The fixed lengths, labelled with their keys.
+                for (names, minutes) in self.config.keybindings.fixed_snoozes() {
+                    let label = match names.first() {
+                        Some(key) => format!("{}m ({})", minutes, key),
+                        None => format!("{}m", minutes),
+                    };
+                    actions = actions.push(button(text(label)).on_press(Message::Snooze(minutes)));
+                }
+            }
+            content = content.push(actions);
+        }
+        let alarms_label = if self.show_alarms { "Hide alarms" } else { "Alarms" };
+        let clocks_label = if self.editing_clocks { "Done" } else { "Edit clocks" };
+        let face_label = match self.config.display.face {
+            ClockFace::Digital => "Analog",
+            ClockFace::Analog => "Digital",
+        };
+        let toolbar = row![
+            button(text(face_label)).on_press(Message::ToggleFace),
+            button(text(clocks_label)).on_press(Message::ToggleClockEditing),
+            button(text(alarms_label)).on_press(Message::ToggleAlarmPanel),
+        ]
+        .spacing(10);
+        let mut content = column![toolbar, content].align_items(Alignment::Center).spacing(10);
+        if self.editing_clocks {
+            content = content.push(self.zone_picker());
+        }
+        content = content.push(clocks);
+        if let Some(status) = self.status.as_ref().filter(|_| !self.show_alarms) {
+            content = content.push(text(status).size(13).style(Color::from_rgb(1.0, 0.3, 0.3)));
+        }
+        if self.show_alarms {
+            content = content.push(self.alarm_panel());
+        }
 
         container(content)
             .width(Length::Fill)
@@ -99,10 +449,175 @@ impl Application for WorldClockApp {
             .into()
     }
     fn subscription(&self) -> Subscription<Message> {
-        iced::time::every(Duration::from_millis(500)).map(|_| {
-            Message::Tick(Local::now().time())
-        })
+        Subscription::batch([
+            iced::time::every(Duration::from_millis(500)).map(|_| Message::Tick),
+            keyboard::on_key_press(key_pressed),
+        ])
+    }
+}
+
+impl WorldClockApp {
+    fn add_clock(&mut self, tz: Tz) {
+        self.clocks.push(Clock { name: tz.name().to_string(), timezone: tz, pinned: false });
+        self.zone_query.clear();
+        self.zone_results.clear();
+        self.save_clocks();
+    }
+
+    /// Writes the current clock list to the profile.
+    fn save_clocks(&mut self) {
+        if !self.session.persist_clocks {
+            self.status = Some("Not saved: clocks came from the command line (use --save)".to_string());
+            return;
+        }
+        let entries: Vec<ClockEntry> = self.clocks.iter().map(Clock::to_entry).collect();
+        let profile = self.session.profile.clone();
+        match config::update_config(|c| {
+            c.profile_mut(&profile).clocks = entries;
+            Ok::<_, ConfigError>(())
+        }) {
+            Ok(config) => {
+                self.config = config;
+                self.status = None;
+            }
+            Err(e) => self.status = Some(format!("Save failed: {}", e)),
+        }
     }
+
+    /// Search field for adding a clock, with the best matches as buttons.
+    fn zone_picker(&self) -> Element<'_, Message> {
+        let mut picker = column![
+            text_input("Add a clock: search zones, cities or abbreviations", &self.zone_query)
+                .on_input(Message::ZoneQuery)
+                .on_submit(Message::AddBestMatch)
+                .width(Length::Fixed(400.0)),
+        ]
+        .spacing(5)
+        .align_items(Alignment::Center);
+        for m in &self.zone_results {
+            picker = picker.push(
+                button(text(m.describe()).size(14))
+                    .on_press(Message::AddClock(m.tz))
+                    .style(iced::theme::Button::Text)
+                    .width(Length::Fixed(400.0)),
+            );
+        }
+        picker.into()
+    }
+
+    /// Silences what is ringing and clears the missed-alarm report.
+    fn dismiss(&mut self) {
+        self.scheduler.dismiss();
+        self.missed.clear();
+    }
+
+    /// Applies `edit` to the alarm entries and reloads the scheduler. Unless
+    /// the alarms came from the command line, the edit is made to the saved
+    /// profile through `config::update_config`, as the `alarm` subcommands do.
+    /// Returns whether the edit was applied.
+    fn edit_alarms(&mut self, edit: impl FnOnce(&mut Vec<AlarmEntry>)) -> bool {
+        let entries = if self.session.persist_alarms {
+            let name = self.session.profile.clone();
+            match config::update_config(|c| {
+                edit(&mut c.profile_mut(&name).alarms);
+                Ok::<_, ConfigError>(())
+            }) {
+                Ok(config) => {
+                    let entries = config.profiles[&name].alarms.clone();
+                    self.config = config;
+                    self.status = None;
+                    entries
+                }
+                Err(e) => {
+                    self.status = Some(format!("Save failed: {}", e));
+                    return false;
+                }
+            }
+        } else {
+            let mut profile = Profile { alarms: self.session.alarms.clone(), ..Profile::default() };
+            edit(&mut profile.alarms);
+            profile.assign_alarm_ids();
+            self.status = Some("Not saved: alarms came from the command line (use --save)".to_string());
+            profile.alarms
+        };
+        match crate::alarms_from_entries(&entries) {
+            Ok(alarms) => self.scheduler.update_alarms(alarms),
+            Err(e) => self.status = Some(e),
+        }
+        self.session.alarms = entries;
+        true
+    }
+
+    /// Every alarm in the profile with an enable toggle and a delete button,
+    /// and fields to add another.
+    fn alarm_panel(&self) -> Element<'_, Message> {
+        let mut list = column![].spacing(5);
+        if self.session.alarms.is_empty() {
+            list = list.push(text("No alarms").size(14).style(Color::from_rgb(0.5, 0.5, 0.5)));
+        }
+        for entry in &self.session.alarms {
+            let id = entry.id;
+            let color = if entry.enabled { Color::WHITE } else { Color::from_rgb(0.5, 0.5, 0.5) };
+            list = list.push(
+                row![
+                    checkbox("", entry.enabled).on_toggle(move |_| Message::ToggleAlarm(id)),
+                    text(format!("#{} {}", id, commands::describe_alarm(entry))).size(14).style(color).width(Length::Fill),
+                    button(text("Delete").size(14)).on_press(Message::RemoveAlarm(id)),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            );
+        }
+
+        let add = row![
+            text_input("HH:MM or HH:MM@Zone", &self.new_time)
+                .on_input(Message::NewAlarmTime)
+                .on_submit(Message::AddAlarm)
+                .width(Length::Fixed(200.0)),
+            text_input("Label (optional)", &self.new_label)
+                .on_input(Message::NewAlarmLabel)
+                .on_submit(Message::AddAlarm)
+                .width(Length::Fixed(200.0)),
+            button(text("Add")).on_press(Message::AddAlarm),
+        ]
+        .spacing(10);
+
+        let mut panel = column![container(scrollable(list)).max_height(240.0), add].spacing(10).max_width(600);
+        if let Some(status) = &self.status {
+            panel = panel.push(text(status).size(13).style(Color::from_rgb(1.0, 0.3, 0.3)));
+        }
+        container(panel).padding(10).style(iced::theme::Container::Box).into()
+    }
+}
+
+/// Turns a key press into a `Message::KeyPressed` with the name keybindings
+/// use for it. Keys typed into a text field never get here.
+fn key_pressed(key: Key, modifiers: Modifiers) -> Option<Message> {
+    let name = match key {
+        Key::Character(c) => c.to_string(),
+        Key::Named(named) => match named {
+            Named::Space => "space",
+            Named::Enter => "enter",
+            Named::Escape => "esc",
+            Named::Tab if modifiers.shift() => "backtab",
+            Named::Tab => "tab",
+            Named::Backspace => "backspace",
+            Named::Delete => "delete",
+            Named::ArrowUp => "up",
+            Named::ArrowDown => "down",
+            Named::ArrowLeft => "left",
+            Named::ArrowRight => "right",
+            _ => return None,
+        }
+        .to_string(),
+        Key::Unidentified => return None,
+    };
+    Some(Message::KeyPressed(name))
+}
+
+/// Single characters are matched case-sensitively, named keys are not, as in the TUI.
+fn bound(names: &[String], key: &str) -> bool {
+    names.iter().any(|name| name == key || (name.chars().count() > 1 && name.eq_ignore_ascii_case(key)))
 }
 
 struct DarkBackground;
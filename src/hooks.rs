@@ -0,0 +1,120 @@
+/*
+ * Commands run when alarms fire. Each one is started through the shell and
+ * left to run while the frontend keeps drawing; `poll` is called from the
+ * tick to reap the ones that finished and kill the ones that overran.
+ */
+
+use crate::alarm::Alarm;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use std::{
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+struct Running {
+    title: String,
+    child: Child,
+    started: Instant,
+}
+
+pub struct HookRunner {
+    timeout: Duration,
+    running: Vec<Running>,
+}
+
+impl HookRunner {
+    pub fn new(timeout: Duration) -> Self {
+        HookRunner { timeout, running: Vec::new() }
+    }
+
+    /// Starts the alarm's command, if it has one, for the alarm firing at `at`.
+    pub fn fire(&mut self, alarm: &Alarm, at: DateTime<Utc>) -> Result<(), String> {
+        let Some(command) = &alarm.command else { return Ok(()) };
+        let (time, zone) = match alarm.zone {
+            Some(tz) => (at.with_timezone(&tz).to_rfc3339_opts(SecondsFormat::Secs, false), tz.name().to_string()),
+            None => (at.with_timezone(&Local).to_rfc3339_opts(SecondsFormat::Secs, false), "local".to_string()),
+        };
+        // The command's output would land on top of the TUI, so it is dropped.
+        let child = shell(command)
+            .env("ALARM_ID", alarm.id.to_string())
+            .env("ALARM_LABEL", alarm.title())
+            .env("ALARM_MESSAGE", alarm.message.as_deref().unwrap_or(""))
+            .env("ALARM_TIME", time)
+            .env("ALARM_ZONE", zone)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Could not run the command for {}: {}", alarm.title(), e))?;
+        self.running.push(Running { title: alarm.title(), child, started: Instant::now() });
+        Ok(())
+    }
+
+    /// Reaps finished commands and kills those past the timeout. Returns a
+    /// line for each one that failed.
+    pub fn poll(&mut self) -> Vec<String> {
+        let mut failures = Vec::new();
+        self.running.retain_mut(|run| match run.child.try_wait() {
+            Ok(Some(status)) => {
+                if !status.success() {
+                    failures.push(format!("Command for {} failed ({})", run.title, status));
+                }
+                false
+            }
+            Ok(None) if run.started.elapsed() >= self.timeout => {
+                kill(&mut run.child);
+                let _ = run.child.wait();
+                failures.push(format!("Command for {} timed out after {}s", run.title, self.timeout.as_secs()));
+                false
+            }
+            Ok(None) => true,
+            Err(e) => {
+                failures.push(format!("Command for {}: {}", run.title, e));
+                false
+            }
+        });
+        failures
+    }
+}
+
+/// Commands still running when the frontend quits are killed rather than
+/// left behind without a timeout.
+impl Drop for HookRunner {
+    fn drop(&mut self) {
+        for run in &mut self.running {
+            kill(&mut run.child);
+            let _ = run.child.wait();
+        }
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn kill(child: &mut Child) {
+    let _ = child.kill();
+}
+
+/// The shell gets a process group of its own so that `kill` also reaches
+/// whatever it started.
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command).process_group(0);
+    shell
+}
+
+#[cfg(unix)]
+fn kill(child: &mut Child) {
+    // SAFETY: plain syscall; a negative pid addresses the child's process
+    // group, which it leads, so nothing outside the hook is signalled.
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+}
@@ -0,0 +1,106 @@
+/*
+ * Fits the terminal UI's clocks to the space it has: how many tiles go in a
+ * row, which lines each tile has room for, and when tiles are given up for a
+ * one-line-per-clock list.
+ */
+
+use crate::bigtext;
+
+/// Tiles look best about this many times wider than tall, counted in cells;
+/// cells are roughly twice as tall as they are wide, so that is about 2:1 on
+/// screen.
+const TILE_ASPECT: f64 = 4.0;
+
+/// Cost of each empty slot at the end of the grid, against the aspect score.
+const EMPTY_SLOT_PENALTY: f64 = 0.3;
+
+/// The smallest tile worth drawing: a border around "00:00:00".
+const MIN_TILE_WIDTH: u16 = 12;
+const MIN_TILE_HEIGHT: u16 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arrangement {
+    Grid { cols: usize, rows: usize },
+    /// One line per clock, for terminals too short for tiles.
+    List,
+}
+
+impl Arrangement {
+    /// Columns for moving focus around: a list is a single column.
+    pub fn cols(&self) -> usize {
+        match self {
+            Arrangement::Grid { cols, .. } => *cols,
+            Arrangement::List => 1,
+        }
+    }
+}
+
+/// Picks the grid whose tiles come closest to `TILE_ASPECT`, or a list if no
+/// grid leaves tiles big enough to draw.
+pub fn arrange(count: usize, width: u16, height: u16) -> Arrangement {
+    if count == 0 {
+        return Arrangement::Grid { cols: 1, rows: 1 };
+    }
+    (1..=count)
+        .filter_map(|cols| {
+            let rows = count.div_ceil(cols);
+            let tile_width = width / cols as u16;
+            let tile_height = height / rows as u16;
+            if tile_width < MIN_TILE_WIDTH || tile_height < MIN_TILE_HEIGHT {
+                return None;
+            }
+            let aspect = tile_width as f64 / tile_height as f64;
+            let score = (aspect / TILE_ASPECT).ln().abs() + (cols * rows - count) as f64 * EMPTY_SLOT_PENALTY;
+            Some((score, Arrangement::Grid { cols, rows }))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or(Arrangement::List, |(_, arrangement)| arrangement)
+}
+
+/// Lines a tile can show besides the time, most wanted first; `fit_tile`
+/// drops them from the end.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TileLine {
+    Date,
+    /// The zone name, for tiles with a custom label.
+    Zone,
+    /// The label or zone again; the border already shows it.
+    Name,
+    /// UTC offset and abbreviation.
+    Offset,
+}
+
+pub struct TileContent {
+    /// The optional lines that fit, in `TileLine` order.
+    pub lines: Vec<TileLine>,
+    /// The time in block digits, if they fit too.
+    pub big: Option<Vec<String>>,
+}
+
+/// Fills a tile's inside (`width` x `height` cells) with the time and as many
+/// of `wanted` as fit. Block digits are preferred over all but the first of
+/// the optional lines.
+pub fn fit_tile(time: &str, wanted: &[TileLine], width: usize, height: usize, big_digits: bool) -> TileContent {
+    let most = wanted.len().min(height.saturating_sub(1));
+    if big_digits {
+        for keep in (most.min(1)..=most).rev() {
+            if let Some(big) = bigtext::render(time, width, height - keep) {
+                return TileContent { lines: wanted[..keep].to_vec(), big: Some(big) };
+            }
+        }
+    }
+    TileContent { lines: wanted[..most].to_vec(), big: None }
+}
+
+/// How many of the optional list columns, with the widths given (most wanted
+/// first), fit after `required` cells, with two spaces before each.
+pub fn fit_columns(required: usize, optional: &[usize], width: usize) -> usize {
+    let mut used = required;
+    optional
+        .iter()
+        .take_while(|column| {
+            used += 2 + **column;
+            used <= width
+        })
+        .count()
+}
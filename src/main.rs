@@ -5,169 +5,275 @@
  *              in a tiled layout, supports local-time alarms, and persists user configuration.
  */
 
+mod alarm;
+mod analog;
+mod audio;
+mod bigtext;
+mod commands;
+mod config;
+mod cron;
 mod tui;
 mod gui;
+mod hooks;
+mod layout;
+mod notify;
+mod outputs;
+mod scheduler;
+mod zones;
 
-use chrono::{NaiveTime, Utc};
+use alarm::Alarm;
+use audio::{AudioSink, CommandPlayer, NullSink, Ringer, TerminalBell};
 use chrono_tz::Tz;
-use clap::Parser;
-use directories::ProjectDirs;
-use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf, time::Duration};
+use clap::{Parser, Subcommand};
+use commands::{AlarmCommand, ClockCommand};
+use config::{AlarmEntry, ClockEntry, Config, Profile};
+use notify::DesktopNotifier;
+use outputs::AlarmOutputs;
+use scheduler::{Scheduler, SystemClock};
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// List of time zones to display (e.g., "America/New_York" "Europe/London")
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    run: RunArgs,
+
+    /// Saved profile to load or edit
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Run in GUI mode (same as the `gui` subcommand)
+    #[arg(long)]
+    gui: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the terminal UI (the default)
+    Run(RunArgs),
+    /// Run the graphical UI
+    Gui(RunArgs),
+    /// Edit or list the saved clocks
+    #[command(subcommand)]
+    Clock(ClockCommand),
+    /// Edit or list the saved alarms
+    #[command(subcommand)]
+    Alarm(AlarmCommand),
+}
+
+#[derive(clap::Args, Debug, Default)]
+struct RunArgs {
+    /// List of time zones to display (e.g., "America/New_York" "Europe/London").
+    /// Prefix with a label to rename the tile: "SF Office=America/Los_Angeles"
     #[arg(num_args = 0..)]
     zones: Vec<String>,
 
-    /// Alarms in HH:MM format (local time)
+    /// Alarms in HH:MM format, in local time or in a zone with HH:MM@Zone,
+    /// optionally labelled (e.g. "Standup=09:00@Asia/Tokyo")
     #[arg(long, num_args = 1..)]
     alarms: Vec<String>,
 
-    /// Run in GUI mode
+    /// Save the zones and alarms given on the command line to the profile
+    /// instead of using them for this run only
     #[arg(long)]
-    gui: bool,
-}
+    save: bool,
 
-#[derive(Serialize, Deserialize)]
-struct StoredClocks(Vec<String>);
-
-#[derive(Serialize, Deserialize)]
-struct StoredAlarms(Vec<String>);
+    /// Show alarms without the bell or sound
+    #[arg(long)]
+    mute: bool,
+}
 
 #[derive(Clone, Debug)] // Added Clone/Debug for Iced
 pub struct Clock {
+    /// The label if one was given, otherwise the zone name.
     pub name: String,
     pub timezone: Tz,
+    pub pinned: bool,
 }
 
-fn get_config_dir() -> Option<PathBuf> {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "rust_world_clock", "rust_world_clock") {
-        let config_dir = proj_dirs.config_dir();
-        if !config_dir.exists() {
-            let _ = fs::create_dir_all(config_dir);
+impl Clock {
+    /// The config entry for this clock; the label is only kept if it differs
+    /// from the zone name.
+    pub fn to_entry(&self) -> ClockEntry {
+        ClockEntry {
+            zone: self.timezone.name().to_string(),
+            label: self.subtitle().map(|_| self.name.clone()),
+            pinned: self.pinned,
         }
-        Some(config_dir.to_path_buf())
-    } else {
-        None
     }
-}
 
-fn save_clocks(zones: &[String]) {
-    if let Some(config_dir) = get_config_dir() {
-        let path = config_dir.join("clocks.json");
-        let stored = StoredClocks(zones.to_vec());
-        if let Ok(json) = serde_json::to_string(&stored) {
-            let _ = fs::write(path, json);
-        }
+    /// The zone name, shown as a subtitle when the clock has a custom label.
+    pub fn subtitle(&self) -> Option<&str> {
+        let zone = self.timezone.name();
+        if zone != self.name { Some(zone) } else { None }
     }
 }
 
-fn load_clocks() -> Vec<String> {
-    if let Some(config_dir) = get_config_dir() {
-        let path = config_dir.join("clocks.json");
-        if let Ok(content) = fs::read_to_string(path) {
-            if let Ok(stored) = serde_json::from_str::<StoredClocks>(&content) {
-                return stored.0;
-            }
-        }
-    }
-    Vec::new()
+/// Resolves saved or CLI clock entries, failing on the first unknown zone.
+/// Pinned clocks are moved to the front, otherwise the order is kept.
+pub fn clocks_from_entries(entries: &[ClockEntry]) -> Result<Vec<Clock>, String> {
+    let mut clocks = entries
+        .iter()
+        .map(|entry| match zones::resolve(&entry.zone) {
+            Ok(tz) => Ok(Clock {
+                name: entry.label.clone().unwrap_or_else(|| tz.name().to_string()),
+                timezone: tz,
+                pinned: entry.pinned,
+            }),
+            Err(e) => Err(e.to_string()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    clocks.sort_by_key(|clock| !clock.pinned);
+    Ok(clocks)
 }
 
-fn save_alarms(alarms: &[NaiveTime]) {
-    if let Some(config_dir) = get_config_dir() {
-        let path = config_dir.join("alarms.json");
-        let alarm_strings: Vec<String> = alarms.iter().map(|t| t.format("%H:%M").to_string()).collect();
-        let stored = StoredAlarms(alarm_strings);
-        if let Ok(json) = serde_json::to_string(&stored) {
-            let _ = fs::write(path, json);
-        }
-    }
+pub fn alarms_from_entries(entries: &[AlarmEntry]) -> Result<Vec<Alarm>, String> {
+    entries
+        .iter()
+        .filter(|alarm| alarm.enabled)
+        .map(Alarm::from_entry)
+        .collect()
 }
 
-fn load_alarms() -> Vec<NaiveTime> {
-    if let Some(config_dir) = get_config_dir() {
-        let path = config_dir.join("alarms.json");
-        if let Ok(content) = fs::read_to_string(path) {
-            if let Ok(stored) = serde_json::from_str::<StoredAlarms>(&content) {
-                return stored.0
-                    .iter()
-                    .filter_map(|s| NaiveTime::parse_from_str(s, "%H:%M").ok())
-                    .collect();
-            }
-        }
-    }
-    Vec::new()
+/// Parses a `[label=]Zone` CLI argument. The zone may be anything
+/// `zones::resolve` understands and is stored as its IANA name.
+pub fn parse_clock_arg(arg: &str) -> Result<ClockEntry, zones::ResolveError> {
+    let (label, zone) = match arg.rsplit_once('=') {
+        Some((label, zone)) if !label.trim().is_empty() => (Some(label.trim().to_string()), zone),
+        Some((_, zone)) => (None, zone),
+        None => (None, arg),
+    };
+    let tz = zones::resolve(zone)?;
+    Ok(ClockEntry { zone: tz.name().to_string(), label, pinned: false })
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
-    
-    // Handle Alarms
-    let mut alarms = Vec::new();
-    if !args.alarms.is_empty() {
-        // Alarms provided via CLI: Parse, use, and save them.
-        for alarm_str in &args.alarms {
-             match NaiveTime::parse_from_str(alarm_str, "%H:%M") {
-                Ok(time) => alarms.push(time),
-                Err(_) => {
-                    eprintln!("Invalid alarm format: {}", alarm_str);
-                    return Ok(());
-                }
-            }
+    let config = match config::load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Failed to load configuration: {}", e);
+            std::process::exit(1);
         }
-        save_alarms(&alarms);
-    } else {
-        // No alarms via CLI: Try to load from config.
-        alarms = load_alarms();
-        if alarms.is_empty() && args.alarms.is_empty() { 
-            // Optional: You could choose to do nothing or set defaults.
-            // For now, empty is fine.
+    };
+
+    let result = match args.command {
+        Some(Command::Clock(cmd)) => commands::clock(cmd, args.profile),
+        Some(Command::Alarm(cmd)) => commands::alarm(cmd, args.profile),
+        Some(Command::Run(run)) => launch(config, run, args.profile, false),
+        Some(Command::Gui(run)) => launch(config, run, args.profile, true),
+        None => launch(config, args.run, args.profile, args.gui),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn launch(mut config: Config, args: RunArgs, profile_arg: Option<String>, gui: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let profile_name = profile_arg.clone().unwrap_or_else(|| config.default_profile.clone());
+    let mut profile = match config.profiles.get(&profile_name) {
+        Some(profile) => profile.clone(),
+        None if profile_arg.is_some() && !args.save => {
+            let mut message = format!("Unknown profile: {}", profile_name);
+            let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+            if !names.is_empty() {
+                message.push_str(&format!("\nSaved profiles: {}", names.join(", ")));
+            }
+            message.push_str("\nUse --save to create it.");
+            return Err(message.into());
         }
+        None => Profile::default(),
+    };
+
+    // Zones and alarms given on the command line replace the profile's for this
+    // run, and are only written back with --save.
+    if !args.alarms.is_empty() {
+        profile.alarms = args.alarms.iter().map(|arg| alarm::parse_alarm_arg(arg)).collect::<Result<_, _>>()?;
+        profile.assign_alarm_ids();
+    }
+    if !args.zones.is_empty() {
+        profile.clocks = args.zones.iter().map(|arg| parse_clock_arg(arg)).collect::<Result<_, _>>()?;
+    }
+
+    // Handle Alarms. Only the profile's saved alarms read and record a
+    // last-evaluated time: alarms given for this run alone did not exist
+    // before it.
+    let alarms = alarms_from_entries(&profile.alarms)?;
+    let saved_alarms = args.alarms.is_empty() || args.save;
+    let mut scheduler = Scheduler::new(alarms, Box::new(SystemClock), config.alarm.fire_missed);
+    if saved_alarms {
+        scheduler = scheduler.resume_from(config::load_last_evaluated(&profile_name));
     }
 
     // Handle Clocks
-    let mut clocks = Vec::new();
-    let zone_strs = if !args.zones.is_empty() {
-        save_clocks(&args.zones);
-        args.zones
-    } else {
-        load_clocks()
-    };
-    
-    let zone_strs = if zone_strs.is_empty() {
+    let entries = if profile.clocks.is_empty() {
         println!("No timezones specified and no configuration found.");
-        println!("To customize, run: cargo run -- <TimeZones...>");
-        println!("Example: cargo run -- America/New_York Europe/London");
+        println!("To customize, run: cargo run -- clock add <TimeZones...>");
+        println!("Example: cargo run -- clock add America/New_York Europe/London");
         println!("Defaulting to Europe/London in 3 seconds...");
         std::thread::sleep(Duration::from_secs(3));
-        vec!["Europe/London".to_string()]
+        vec![ClockEntry { zone: "Europe/London".to_string(), label: None, pinned: false }]
     } else {
-        zone_strs
+        profile.clocks.clone()
     };
+    let clocks = clocks_from_entries(&entries)?;
 
-    for zone_str in zone_strs {
-        match zone_str.parse::<Tz>() {
-            Ok(tz) => {
-                clocks.push(Clock {
-                    name: zone_str,
-                    timezone: tz,
-                });
+    if args.save {
+        let saved = profile.clone();
+        config = config::update_config(|c| {
+            let target = c.profile_mut(&profile_name);
+            if !args.zones.is_empty() {
+                target.clocks = saved.clocks;
             }
-            Err(_) => {
-                eprintln!("Invalid time zone: {}", zone_str);
-                return Ok(());
+            if !args.alarms.is_empty() {
+                target.alarms = saved.alarms;
             }
-        }
+            Ok::<_, config::ConfigError>(())
+        })?;
     }
 
-    if args.gui {
-        gui::run(clocks, alarms)?;
+    // The TUI can only ring the terminal bell; the GUI plays sound files if
+    // there is a player to do it.
+    let sink: Box<dyn AudioSink> = if args.mute {
+        Box::new(NullSink)
+    } else if gui {
+        match CommandPlayer::detect() {
+            Some(player) => Box::new(player),
+            None => {
+                eprintln!("Alarm sounds are unavailable: no sound player found (tried {})", audio::player_names());
+                Box::new(NullSink)
+            }
+        }
+    } else {
+        Box::new(TerminalBell)
+    };
+    let notifier = if config.alarm.notify {
+        DesktopNotifier::connect()
+            .map_err(|e| eprintln!("Desktop notifications are unavailable: {}", e))
+            .ok()
+    } else {
+        None
+    };
+    let outputs = AlarmOutputs::new(Ringer::new(sink), notifier, &config.alarm);
+
+    // Edits made in either UI only go back to the profile if its clocks did.
+    let persist = args.zones.is_empty() || args.save;
+    if gui {
+        let session = gui::Session {
+            profile: profile_name,
+            alarms: profile.alarms,
+            persist_clocks: persist,
+            persist_alarms: saved_alarms,
+        };
+        gui::run(clocks, scheduler, outputs, config, session)?;
     } else {
-        tui::run(&clocks, &alarms)?;
+        tui::run(clocks, scheduler, outputs, config, &profile_name, persist)?;
     }
 
     Ok(())
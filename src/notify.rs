@@ -0,0 +1,306 @@
+/*
+ * Desktop notifications for alarms over the freedesktop
+ * `org.freedesktop.Notifications` D-Bus interface, with Dismiss and Snooze
+ * buttons whose clicks are handed back to the frontend for the scheduler.
+ *
+ * The session bus is found through DBUS_SESSION_BUS_ADDRESS as usual, so
+ * the ignored test at the bottom, which brings its own stand-in notification
+ * server, runs on a private bus without touching the real desktop:
+ * `dbus-run-session -- cargo test -- --ignored`.
+ */
+
+use crate::alarm::Alarm;
+use std::{
+    collections::HashMap,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::Value;
+
+const DESTINATION: &str = "org.freedesktop.Notifications";
+const PATH: &str = "/org/freedesktop/Notifications";
+const INTERFACE: &str = "org.freedesktop.Notifications";
+const APP_NAME: &str = "Rust World Clock";
+
+/// Urgency hint values from the notification spec.
+const URGENCY_CRITICAL: u8 = 2;
+
+/// A button the user clicked on an alarm's notification.
+#[derive(Debug, PartialEq)]
+pub enum NotificationAction {
+    Dismiss(u32),
+    Snooze(u32),
+}
+
+/// Work for the thread that makes the (blocking) D-Bus calls.
+enum Request {
+    Show { alarm: u32, summary: String, body: String, snooze_minutes: u32 },
+    Close(u32),
+}
+
+/// What the bus threads report back.
+enum Reply {
+    Shown { notification: u32, alarm: u32 },
+    Failed(String),
+    /// An `ActionInvoked` signal: notification id and action key.
+    Invoked(u32, String),
+}
+
+/// Talks to the notification server from threads of its own, so that a slow
+/// or stuck server never holds up drawing.
+pub struct DesktopNotifier {
+    requests: Sender<Request>,
+    replies: Receiver<Reply>,
+    /// Notification id to alarm id, for notifications still on screen.
+    shown: HashMap<u32, u32>,
+    clicked: Vec<NotificationAction>,
+    failures: Vec<String>,
+}
+
+impl DesktopNotifier {
+    /// Connects to the session bus and starts listening for action clicks.
+    pub fn connect() -> zbus::Result<DesktopNotifier> {
+        let connection = Connection::session()?;
+        let proxy = Proxy::new(&connection, DESTINATION, PATH, INTERFACE)?;
+        let signals = proxy.receive_signal("ActionInvoked")?;
+        let (replies_to, replies) = mpsc::channel();
+
+        let invoked = replies_to.clone();
+        thread::spawn(move || {
+            for message in signals {
+                let Ok((notification, key)) = message.body().deserialize::<(u32, String)>() else { continue };
+                if invoked.send(Reply::Invoked(notification, key)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (requests, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            for request in incoming {
+                let reply = match request {
+                    Request::Show { alarm, summary, body, snooze_minutes } => {
+                        match show(&proxy, &summary, &body, snooze_minutes) {
+                            Ok(notification) => Reply::Shown { notification, alarm },
+                            Err(e) => Reply::Failed(format!("Notification failed: {}", e)),
+                        }
+                    }
+                    Request::Close(notification) => {
+                        let _: zbus::Result<()> = proxy.call("CloseNotification", &(notification,));
+                        continue;
+                    }
+                };
+                if replies_to.send(reply).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(DesktopNotifier { requests, replies, shown: HashMap::new(), clicked: Vec::new(), failures: Vec::new() })
+    }
+
+    /// Shows a notification for an alarm that just started ringing.
+    pub fn notify(&mut self, alarm: &Alarm, snooze_minutes: u32) {
+        let body = match &alarm.message {
+            Some(message) => message.clone(),
+            None => alarm.summary(),
+        };
+        let _ = self.requests.send(Request::Show { alarm: alarm.id, summary: alarm.title(), body, snooze_minutes });
+    }
+
+    /// Buttons clicked since the last call.
+    pub fn actions(&mut self) -> Vec<NotificationAction> {
+        self.receive();
+        std::mem::take(&mut self.clicked)
+    }
+
+    /// Notifications that could not be shown since the last call.
+    pub fn failures(&mut self) -> Vec<String> {
+        self.receive();
+        std::mem::take(&mut self.failures)
+    }
+
+    /// Closes notifications for alarms that are no longer ringing, e.g.
+    /// because they were dismissed in the UI.
+    pub fn retain(&mut self, ringing: &[&Alarm]) {
+        self.receive();
+        let stale: Vec<u32> = self
+            .shown
+            .iter()
+            .filter(|(_, alarm)| !ringing.iter().any(|r| r.id == **alarm))
+            .map(|(notification, _)| *notification)
+            .collect();
+        for notification in stale {
+            self.shown.remove(&notification);
+            let _ = self.requests.send(Request::Close(notification));
+        }
+    }
+
+    fn receive(&mut self) {
+        while let Ok(reply) = self.replies.try_recv() {
+            match reply {
+                Reply::Shown { notification, alarm } => {
+                    self.shown.insert(notification, alarm);
+                }
+                Reply::Failed(failure) => self.failures.push(failure),
+                // Signals for other applications' notifications arrive too.
+                Reply::Invoked(notification, key) => {
+                    if let Some(alarm) = self.shown.remove(&notification) {
+                        self.clicked.extend(action(alarm, &key));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Sends one notification and returns the id the server gave it.
+fn show(proxy: &Proxy, summary: &str, body: &str, snooze_minutes: u32) -> zbus::Result<u32> {
+    let snooze = format!("Snooze {}m", snooze_minutes);
+    let actions = ["dismiss", "Dismiss", "snooze", snooze.as_str()];
+    let hints = HashMap::from([("urgency", Value::U8(URGENCY_CRITICAL))]);
+    // A timeout of 0 keeps the notification up until it is acted on.
+    proxy.call("Notify", &(APP_NAME, 0u32, "alarm-symbolic", summary, body, &actions[..], hints, 0i32))
+}
+
+/// What clicking the button with action `key` on `alarm`'s notification
+/// means. Other keys, such as "default" for a click on the notification
+/// itself, mean nothing.
+fn action(alarm: u32, key: &str) -> Option<NotificationAction> {
+    match key {
+        "dismiss" => Some(NotificationAction::Dismiss(alarm)),
+        "snooze" => Some(NotificationAction::Snooze(alarm)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+    use zbus::{interface, object_server::SignalEmitter, zvariant::OwnedValue};
+
+    #[test]
+    fn action_keys_map_to_scheduler_actions() {
+        assert_eq!(action(3, "dismiss"), Some(NotificationAction::Dismiss(3)));
+        assert_eq!(action(3, "snooze"), Some(NotificationAction::Snooze(3)));
+        assert_eq!(action(3, "default"), None);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Notified {
+        summary: String,
+        body: String,
+        actions: Vec<String>,
+        urgency: Option<u8>,
+        timeout: i32,
+    }
+
+    /// Stand-in for the desktop's notification server, recording what it is asked.
+    #[derive(Default)]
+    struct FakeServer {
+        notified: Arc<Mutex<Vec<Notified>>>,
+        closed: Arc<Mutex<Vec<u32>>>,
+    }
+
+    #[interface(name = "org.freedesktop.Notifications")]
+    impl FakeServer {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: String,
+            _replaces_id: u32,
+            _icon: String,
+            summary: String,
+            body: String,
+            actions: Vec<String>,
+            hints: HashMap<String, OwnedValue>,
+            timeout: i32,
+        ) -> u32 {
+            let urgency = hints.get("urgency").and_then(|value| u8::try_from(value).ok());
+            let mut notified = self.notified.lock().unwrap();
+            notified.push(Notified { summary, body, actions, urgency, timeout });
+            notified.len() as u32
+        }
+
+        fn close_notification(&self, id: u32) {
+            self.closed.lock().unwrap().push(id);
+        }
+
+        #[zbus(signal)]
+        async fn action_invoked(emitter: &SignalEmitter<'_>, id: u32, action_key: String) -> zbus::Result<()>;
+    }
+
+    /// Notifications go out from the notifier's own thread; waits until the
+    /// server has answered for `count` of them.
+    fn wait_until_shown(notifier: &mut DesktopNotifier, count: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while notifier.shown.len() < count && Instant::now() < deadline {
+            notifier.receive();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(notifier.shown.len(), count);
+    }
+
+    #[test]
+    #[ignore = "needs a private session bus: dbus-run-session -- cargo test -- --ignored"]
+    fn round_trip_through_a_stand_in_server() {
+        let server = FakeServer::default();
+        let (notified, closed) = (server.notified.clone(), server.closed.clone());
+        let connection = zbus::blocking::connection::Builder::session()
+            .and_then(|builder| builder.name(DESTINATION))
+            .and_then(|builder| builder.serve_at(PATH, server))
+            .and_then(|builder| builder.build())
+            .unwrap();
+
+        let mut notifier = DesktopNotifier::connect().unwrap();
+        notifier.notify(&Alarm::for_test(1, "Standup", |entry| entry.message = Some("Join the call".to_string())), 5);
+        notifier.notify(&Alarm::for_test(2, "Review", |_| {}), 10);
+        wait_until_shown(&mut notifier, 2);
+        {
+            let notified = notified.lock().unwrap();
+            assert_eq!(
+                notified[0],
+                Notified {
+                    summary: "Standup".to_string(),
+                    body: "Join the call".to_string(),
+                    actions: ["dismiss", "Dismiss", "snooze", "Snooze 5m"].map(String::from).to_vec(),
+                    urgency: Some(URGENCY_CRITICAL),
+                    timeout: 0,
+                }
+            );
+            assert_eq!(notified[1].body, "09:00");
+            assert_eq!(notified[1].actions[3], "Snooze 10m");
+        }
+
+        // Notification 99 is not ours and must be ignored.
+        let interface = connection.object_server().interface::<_, FakeServer>(PATH).unwrap();
+        for (id, key) in [(99, "dismiss"), (1, "snooze"), (2, "dismiss")] {
+            zbus::block_on(FakeServer::action_invoked(interface.signal_emitter(), id, key.to_string())).unwrap();
+        }
+        let mut actions = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while actions.len() < 2 && Instant::now() < deadline {
+            actions.extend(notifier.actions());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(actions, [NotificationAction::Snooze(1), NotificationAction::Dismiss(2)]);
+
+        let standup = Alarm::for_test(1, "Standup", |_| {});
+        notifier.notify(&standup, 5);
+        wait_until_shown(&mut notifier, 1);
+        notifier.retain(&[&standup]);
+        assert!(notifier.shown.contains_key(&3));
+        notifier.retain(&[]);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while closed.lock().unwrap().is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(*closed.lock().unwrap(), [3]);
+        assert!(notifier.failures().is_empty());
+    }
+}
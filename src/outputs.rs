@@ -0,0 +1,67 @@
+/*
+ * What alarms do outside the window: the bell or sound, the desktop
+ * notification and the alarm's command. Both frontends hand every tick's
+ * scheduler events to `AlarmOutputs::handle`, so they ring, notify and run
+ * hooks the same way and only differ in how they draw.
+ */
+
+use crate::audio::Ringer;
+use crate::config::AlarmPrefs;
+use crate::hooks::HookRunner;
+use crate::notify::{DesktopNotifier, NotificationAction};
+use crate::scheduler::{AlarmEvent, Scheduler};
+use std::time::Duration;
+
+pub struct AlarmOutputs {
+    ringer: Ringer,
+    notifier: Option<DesktopNotifier>,
+    hooks: HookRunner,
+    /// Used by the notification's Snooze button.
+    snooze_minutes: u32,
+}
+
+impl AlarmOutputs {
+    pub fn new(ringer: Ringer, notifier: Option<DesktopNotifier>, prefs: &AlarmPrefs) -> Self {
+        AlarmOutputs {
+            ringer,
+            notifier,
+            hooks: HookRunner::new(Duration::from_secs(prefs.command_timeout_seconds)),
+            snooze_minutes: prefs.snooze_minutes,
+        }
+    }
+
+    /// Notifies and runs commands for alarms that fired, applies clicked
+    /// notification buttons to the scheduler, then brings the sound and
+    /// notifications in line with what is still ringing. Returns failures
+    /// for the frontend's status line.
+    pub fn handle(&mut self, scheduler: &mut Scheduler, events: &[AlarmEvent]) -> Vec<String> {
+        let mut failures = Vec::new();
+        for event in events {
+            let AlarmEvent::Fired(alarm) = event else { continue };
+            if let Some(notifier) = &mut self.notifier {
+                notifier.notify(alarm, self.snooze_minutes);
+            }
+            if let Err(e) = self.hooks.fire(alarm, scheduler.now()) {
+                failures.push(e);
+            }
+        }
+        failures.extend(self.hooks.poll());
+
+        if let Some(notifier) = &mut self.notifier {
+            failures.extend(notifier.failures());
+            for action in notifier.actions() {
+                match action {
+                    NotificationAction::Dismiss(id) => scheduler.dismiss_alarm(id),
+                    NotificationAction::Snooze(id) => scheduler.snooze_alarm(id, self.snooze_minutes),
+                }
+            }
+        }
+
+        let ringing = scheduler.ringing(scheduler.now());
+        self.ringer.update(&ringing);
+        if let Some(notifier) = &mut self.notifier {
+            notifier.retain(&ringing);
+        }
+        failures
+    }
+}
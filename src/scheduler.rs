@@ -0,0 +1,438 @@
+/*
+ * Alarm engine shared by the TUI and the GUI: which alarms are ringing
+ * right now, which of those the user has dismissed or snoozed, and which
+ * went off while nothing was watching (the machine was asleep, or the app
+ * was not running).
+ *
+ * Time comes from a `TimeSource` rather than the system clock directly, and
+ * frontends learn about changes from the `AlarmEvent`s returned by `tick`.
+ */
+
+use crate::alarm::{Alarm, LocalZone};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::collections::{HashMap, HashSet};
+
+/// How far back to look for missed alarms after a long gap.
+const MISSED_LOOKBACK_MINUTES: i64 = 7 * 24 * 60;
+
+/// Where the scheduler (and the frontends drawing with it) get "now" from.
+pub trait TimeSource {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// What alarms without a zone of their own count as local time.
+    fn local_zone(&self) -> LocalZone {
+        LocalZone::System
+    }
+}
+
+/// The real system clock.
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to, reading local time in a given
+/// zone, so tests can step through alarms minute by minute.
+#[cfg(test)]
+#[derive(Clone)]
+pub struct ManualClock {
+    now: std::sync::Arc<std::sync::Mutex<DateTime<Utc>>>,
+    local: chrono_tz::Tz,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new(now: DateTime<Utc>, local: chrono_tz::Tz) -> Self {
+        ManualClock { now: std::sync::Arc::new(std::sync::Mutex::new(now)), local }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+#[cfg(test)]
+impl TimeSource for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+
+    fn local_zone(&self) -> LocalZone {
+        LocalZone::Zone(self.local)
+    }
+}
+
+/// Something a frontend may want to react to, returned by `Scheduler::tick`.
+pub enum AlarmEvent {
+    /// An alarm started ringing: on schedule, after a snooze, or late.
+    Fired(Alarm),
+    /// An alarm went off while nothing was watching.
+    Missed(Missed),
+    /// Alarms have been evaluated up to this time; persist it so the next
+    /// run can look for missed alarms.
+    Evaluated(DateTime<Utc>),
+}
+
+pub struct Scheduler {
+    alarms: Vec<Alarm>,
+    clock: Box<dyn TimeSource>,
+    /// What the user has done about each alarm, keyed by `Alarm::id`.
+    states: HashMap<u32, AlarmState>,
+    /// Last time `tick` ran; minutes between it and the next tick were not
+    /// watched.
+    last_evaluated: Option<DateTime<Utc>>,
+    /// Whether `tick` reports `Evaluated` checkpoints; off unless resuming
+    /// a profile's saved alarms.
+    checkpoints: bool,
+    /// Alarms that were ringing at the last tick, to spot new ones.
+    was_ringing: HashSet<u32>,
+    /// Whether missed alarms ring when noticed, rather than only being reported.
+    fire_missed: bool,
+}
+
+#[derive(Default)]
+struct AlarmState {
+    /// Minute (as minutes since the epoch) in which the alarm was dismissed.
+    dismissed_minute: Option<i64>,
+    /// When a snoozed (or late-fired missed) alarm rings again. It keeps
+    /// ringing from then until it is dismissed or snoozed again.
+    rings_again_at: Option<DateTime<Utc>>,
+}
+
+/// An alarm that went off while nothing was watching.
+pub struct Missed {
+    pub title: String,
+    pub at: DateTime<Utc>,
+    /// Zone `describe` shows the time in.
+    local: LocalZone,
+}
+
+impl Missed {
+    /// "Standup at 09:00", with the date too if it was not today.
+    pub fn describe(&self, now: DateTime<Utc>) -> String {
+        let at = self.local.wall(self.at);
+        let format = if at.date() == self.local.wall(now).date() { "%H:%M" } else { "%Y-%m-%d %H:%M" };
+        format!("{} at {}", self.title, at.format(format))
+    }
+}
+
+impl Scheduler {
+    pub fn new(alarms: Vec<Alarm>, clock: Box<dyn TimeSource>, fire_missed: bool) -> Self {
+        Scheduler {
+            alarms,
+            clock,
+            states: HashMap::new(),
+            last_evaluated: None,
+            checkpoints: false,
+            was_ringing: HashSet::new(),
+            fire_missed,
+        }
+    }
+
+    /// Continues from a previous run that last evaluated alarms at
+    /// `last_evaluated` (if known), and reports checkpoints from now on.
+    pub fn resume_from(mut self, last_evaluated: Option<DateTime<Utc>>) -> Self {
+        self.last_evaluated = last_evaluated;
+        self.checkpoints = true;
+        self
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    pub fn alarms(&self) -> &[Alarm] {
+        &self.alarms
+    }
+
+    /// Switches to another profile's alarms, which a previous run last
+    /// evaluated at `last_evaluated`, so those missed in the meantime are
+    /// found on the next tick. Without checkpoints the saved time does not
+    /// apply, and the scheduler keeps its own.
+    pub fn set_alarms(&mut self, alarms: Vec<Alarm>, last_evaluated: Option<DateTime<Utc>>) {
+        self.alarms = alarms;
+        self.states.clear();
+        if self.checkpoints {
+            self.last_evaluated = last_evaluated;
+        }
+    }
+
+    /// Replaces the alarm set after alarms were added, removed or toggled,
+    /// keeping the dismissed or snoozed state of those still present.
+    pub fn update_alarms(&mut self, alarms: Vec<Alarm>) {
+        self.states.retain(|id, _| alarms.iter().any(|alarm| alarm.id == *id));
+        self.alarms = alarms;
+    }
+
+    /// Advances the scheduler to the current time and reports what changed:
+    /// alarms that started ringing, alarms that fired in any minutes skipped
+    /// since the last tick, and (once per minute) a checkpoint to persist.
+    pub fn tick(&mut self) -> Vec<AlarmEvent> {
+        let now = self.now();
+        let minute = minute_of(now);
+        let mut events = Vec::new();
+        let last = self.last_evaluated.map(minute_of);
+        if last != Some(minute) {
+            if let Some(last) = last
+                && last + 1 < minute
+            {
+                events.extend(self.find_missed(last + 1, minute, now).into_iter().map(AlarmEvent::Missed));
+            }
+            self.last_evaluated = Some(now);
+            if self.checkpoints {
+                events.push(AlarmEvent::Evaluated(now));
+            }
+        }
+
+        let ringing: Vec<Alarm> = self.ringing(now).into_iter().cloned().collect();
+        let was_ringing = std::mem::take(&mut self.was_ringing);
+        for alarm in ringing {
+            self.was_ringing.insert(alarm.id);
+            if !was_ringing.contains(&alarm.id) {
+                events.push(AlarmEvent::Fired(alarm));
+            }
+        }
+        events
+    }
+
+    /// Records alarms that fired in minutes `from..to`, at most once each.
+    fn find_missed(&mut self, from: i64, to: i64, now: DateTime<Utc>) -> Vec<Missed> {
+        let from = from.max(to - MISSED_LOOKBACK_MINUTES);
+        let local = self.clock.local_zone();
+        let mut latest: HashMap<u32, DateTime<Utc>> = HashMap::new();
+        for minute in from..to {
+            let Some(at) = Utc.timestamp_opt(minute * 60, 0).single() else { continue };
+            for alarm in self.alarms.iter().filter(|alarm| alarm.is_ringing(at, local)) {
+                latest.insert(alarm.id, at);
+            }
+        }
+        let mut found = Vec::new();
+        for alarm in &self.alarms {
+            let Some(&at) = latest.get(&alarm.id) else { continue };
+            found.push(Missed { title: alarm.title(), at, local });
+            if self.fire_missed {
+                self.states.entry(alarm.id).or_default().rings_again_at = Some(now);
+            }
+        }
+        found
+    }
+
+    /// Alarms ringing at `now` that have not been dismissed or snoozed.
+    pub fn ringing(&self, now: DateTime<Utc>) -> Vec<&Alarm> {
+        self.alarms.iter().filter(|alarm| self.is_ringing(alarm, now)).collect()
+    }
+
+    fn is_ringing(&self, alarm: &Alarm, now: DateTime<Utc>) -> bool {
+        let state = self.states.get(&alarm.id);
+        if let Some(at) = state.and_then(|s| s.rings_again_at) {
+            return now >= at;
+        }
+        // A dismissal only lasts for the minute it was made in.
+        alarm.is_ringing(now, self.clock.local_zone()) && state.and_then(|s| s.dismissed_minute) != Some(minute_of(now))
+    }
+
+    /// Alarms currently snoozed, with when each will ring again.
+    pub fn snoozed(&self, now: DateTime<Utc>) -> Vec<(&Alarm, DateTime<Utc>)> {
+        self.alarms
+            .iter()
+            .filter_map(|alarm| self.snoozed_until(alarm, now).map(|until| (alarm, until)))
+            .collect()
+    }
+
+    /// When `alarm` will ring again, if it is currently snoozed.
+    pub fn snoozed_until(&self, alarm: &Alarm, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.states
+            .get(&alarm.id)
+            .and_then(|s| s.rings_again_at)
+            .filter(|until| *until > now)
+    }
+
+    /// Silences everything ringing.
+    pub fn dismiss(&mut self) {
+        for id in self.ringing_ids(self.now()) {
+            self.dismiss_alarm(id);
+        }
+    }
+
+    /// Puts off everything ringing for `minutes`, after which each alarm
+    /// rings again until dismissed.
+    pub fn snooze(&mut self, minutes: u32) {
+        for id in self.ringing_ids(self.now()) {
+            self.snooze_alarm(id, minutes);
+        }
+    }
+
+    /// Silences one alarm, e.g. from a notification's action button.
+    pub fn dismiss_alarm(&mut self, id: u32) {
+        let now = self.now();
+        let state = self.states.entry(id).or_default();
+        state.rings_again_at = None;
+        state.dismissed_minute = Some(minute_of(now));
+    }
+
+    /// Puts off one alarm for `minutes`.
+    pub fn snooze_alarm(&mut self, id: u32, minutes: u32) {
+        let now = self.now();
+        let state = self.states.entry(id).or_default();
+        state.rings_again_at = Some(now + Duration::minutes(minutes.into()));
+        // Once the snooze ends, the original minute must not ring again.
+        state.dismissed_minute = Some(minute_of(now));
+    }
+
+    fn ringing_ids(&self, now: DateTime<Utc>) -> Vec<u32> {
+        self.ringing(now).iter().map(|alarm| alarm.id).collect()
+    }
+}
+
+fn minute_of(now: DateTime<Utc>) -> i64 {
+    now.timestamp().div_euclid(60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Repeat;
+    use chrono_tz::Tz;
+
+    /// Stands in for local time; 09:00 there is 00:00 UTC.
+    const LOCAL: Tz = chrono_tz::Asia::Tokyo;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    fn scheduler(alarms: Vec<Alarm>, start: &str, fire_missed: bool) -> (Scheduler, ManualClock) {
+        let clock = ManualClock::new(at(start), LOCAL);
+        (Scheduler::new(alarms, Box::new(clock.clone()), fire_missed), clock)
+    }
+
+    fn fired(events: &[AlarmEvent]) -> Vec<u32> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                AlarmEvent::Fired(alarm) => Some(alarm.id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn missed(events: &[AlarmEvent], now: DateTime<Utc>) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                AlarmEvent::Missed(missed) => Some(missed.describe(now)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fires_once_when_its_minute_starts() {
+        let (mut scheduler, clock) = scheduler(vec![Alarm::for_test(1, "Standup", |_| {})], "2026-03-01T23:59:30Z", false);
+        assert!(fired(&scheduler.tick()).is_empty());
+
+        clock.set(at("2026-03-02T00:00:05Z"));
+        assert_eq!(fired(&scheduler.tick()), [1]);
+
+        clock.advance(Duration::seconds(20));
+        assert!(fired(&scheduler.tick()).is_empty());
+        assert_eq!(scheduler.ringing(scheduler.now()).len(), 1);
+
+        clock.set(at("2026-03-02T00:01:00Z"));
+        scheduler.tick();
+        assert!(scheduler.ringing(scheduler.now()).is_empty());
+    }
+
+    #[test]
+    fn dismissal_lasts_for_the_rest_of_the_minute() {
+        let (mut scheduler, clock) = scheduler(vec![Alarm::for_test(1, "Standup", |_| {})], "2026-03-02T00:00:05Z", false);
+        assert_eq!(fired(&scheduler.tick()), [1]);
+        scheduler.dismiss();
+        assert!(scheduler.ringing(scheduler.now()).is_empty());
+
+        clock.advance(Duration::seconds(30));
+        assert!(fired(&scheduler.tick()).is_empty());
+        assert!(scheduler.ringing(scheduler.now()).is_empty());
+
+        clock.set(at("2026-03-03T00:00:00Z"));
+        assert_eq!(fired(&scheduler.tick()), [1]);
+    }
+
+    #[test]
+    fn snoozed_alarm_rings_again_until_dismissed() {
+        let (mut scheduler, clock) = scheduler(vec![Alarm::for_test(1, "Standup", |_| {})], "2026-03-02T00:00:05Z", false);
+        scheduler.tick();
+        scheduler.snooze(5);
+        assert!(scheduler.ringing(scheduler.now()).is_empty());
+
+        clock.set(at("2026-03-02T00:02:00Z"));
+        assert!(fired(&scheduler.tick()).is_empty());
+        let snoozed = scheduler.snoozed(scheduler.now());
+        assert_eq!(snoozed.len(), 1);
+        assert_eq!(snoozed[0].1, at("2026-03-02T00:05:05Z"));
+
+        clock.set(at("2026-03-02T00:05:05Z"));
+        assert_eq!(fired(&scheduler.tick()), [1]);
+
+        clock.set(at("2026-03-02T00:07:00Z"));
+        assert!(fired(&scheduler.tick()).is_empty());
+        assert_eq!(scheduler.ringing(scheduler.now()).len(), 1);
+
+        scheduler.dismiss();
+        assert!(scheduler.ringing(scheduler.now()).is_empty());
+    }
+
+    #[test]
+    fn alarms_elapsed_while_away_are_reported_once() {
+        let (scheduler, _clock) = scheduler(vec![Alarm::for_test(1, "Standup", |_| {})], "2026-03-02T01:00:00Z", false);
+        let mut scheduler = scheduler.resume_from(Some(at("2026-03-01T23:00:00Z")));
+        let events = scheduler.tick();
+        assert_eq!(missed(&events, scheduler.now()), ["Standup at 09:00"]);
+        assert!(fired(&events).is_empty());
+        assert!(events.iter().any(|event| matches!(event, AlarmEvent::Evaluated(_))));
+        assert!(scheduler.ringing(scheduler.now()).is_empty());
+
+        assert!(missed(&scheduler.tick(), scheduler.now()).is_empty());
+    }
+
+    #[test]
+    fn fire_missed_rings_late() {
+        let (scheduler, _clock) = scheduler(vec![Alarm::for_test(1, "Standup", |_| {})], "2026-03-02T01:00:00Z", true);
+        let mut scheduler = scheduler.resume_from(Some(at("2026-03-01T23:00:00Z")));
+        let events = scheduler.tick();
+        assert_eq!(missed(&events, scheduler.now()), ["Standup at 09:00"]);
+        assert_eq!(fired(&events), [1]);
+
+        scheduler.dismiss();
+        assert!(scheduler.ringing(scheduler.now()).is_empty());
+    }
+
+    #[test]
+    fn switching_profiles_resumes_from_the_new_profiles_checkpoint() {
+        let (scheduler, _clock) = scheduler(Vec::new(), "2026-03-02T01:00:00Z", false);
+        let mut scheduler = scheduler.resume_from(Some(at("2026-03-02T00:59:00Z")));
+        assert!(missed(&scheduler.tick(), scheduler.now()).is_empty());
+
+        let oncall = vec![Alarm::for_test(1, "Handover", |_| {})];
+        scheduler.set_alarms(oncall, Some(at("2026-03-01T23:00:00Z")));
+        assert_eq!(missed(&scheduler.tick(), scheduler.now()), ["Handover at 09:00"]);
+    }
+
+    #[test]
+    fn missed_alarms_are_looked_for_over_the_last_week_only() {
+        let once = |id, label: &str, date: &str| {
+            Alarm::for_test(id, label, |entry| entry.repeat = Repeat::Once { date: date.to_string() })
+        };
+        let alarms = vec![once(1, "Recent", "2026-03-01"), once(2, "Old", "2026-02-27")];
+        let (scheduler, _clock) = scheduler(alarms, "2026-03-07T03:00:00Z", false);
+        let mut scheduler = scheduler.resume_from(Some(at("2026-02-20T00:00:00Z")));
+        assert_eq!(missed(&scheduler.tick(), scheduler.now()), ["Recent at 2026-03-01 09:00"]);
+    }
+}
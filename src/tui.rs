@@ -1,5 +1,12 @@
+use crate::alarm::Alarm;
+use crate::layout::{self, Arrangement, TileLine};
+use crate::scheduler::{AlarmEvent, Missed, Scheduler};
+use crate::config::{self, ClockEntry, Config, DisplayPrefs};
+use crate::outputs::AlarmOutputs;
+use crate::zones::{self, Match};
 use crate::Clock;
-use chrono::{Local, NaiveTime, Timelike, Utc};
+use chrono::{DateTime, Local, Offset, Utc};
+use chrono_tz::{OffsetComponents, Tz};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     execute,
@@ -7,11 +14,197 @@ use crossterm::{
 };
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph},
 };
-use std::{io, time::Duration};
+use std::{cell::Cell, io, time::Duration};
 
-pub fn run(clocks: &[Clock], alarms: &[NaiveTime]) -> Result<(), Box<dyn std::error::Error>> {
+/// Maximum number of picker results kept per keystroke.
+const PICKER_RESULTS: usize = 100;
+
+/// State owned by the TUI while it runs.
+struct App {
+    config: Config,
+    profile: String,
+    clocks: Vec<Clock>,
+    scheduler: Scheduler,
+    outputs: AlarmOutputs,
+    /// Missed alarms reported by the scheduler, until dismissed.
+    missed: Vec<Missed>,
+    /// Index into `clocks` of the tile that per-tile keys act on.
+    focus: usize,
+    /// Columns in the layout last drawn, for moving focus with the arrows.
+    grid_cols: Cell<usize>,
+    /// Whether clock edits are written back to the profile. False when the
+    /// clocks came from the command line without `--save`.
+    persist: bool,
+    mode: Mode,
+    /// Transient message shown in the status bar, e.g. a profile load error.
+    status: Option<String>,
+}
+
+enum Mode {
+    Normal,
+    AddClock(Picker),
+    /// Editing the focused tile's label; holds the text typed so far.
+    Rename(String),
+    /// Information popup for the focused tile; any key closes it.
+    Details,
+}
+
+/// Incremental zone search shown in the "add clock" modal.
+struct Picker {
+    query: String,
+    results: Vec<Match>,
+    selected: usize,
+}
+
+impl Picker {
+    fn new() -> Self {
+        Picker { query: String::new(), results: zones::search("", PICKER_RESULTS), selected: 0 }
+    }
+
+    fn refresh(&mut self) {
+        self.results = zones::search(&self.query, PICKER_RESULTS);
+        self.selected = 0;
+    }
+}
+
+impl App {
+    /// Switches to the saved profile after the current one, wrapping around.
+    fn next_profile(&mut self) {
+        let names: Vec<&String> = self.config.profiles.keys().collect();
+        if names.is_empty() {
+            return;
+        }
+        let next = match names.iter().position(|name| **name == self.profile) {
+            Some(i) => names[(i + 1) % names.len()],
+            None => names[0],
+        };
+        let profile = &self.config.profiles[next];
+        match (crate::clocks_from_entries(&profile.clocks), crate::alarms_from_entries(&profile.alarms)) {
+            (Ok(clocks), Ok(alarms)) => {
+                self.clocks = clocks;
+                self.scheduler.set_alarms(alarms, config::load_last_evaluated(next));
+                self.status = None;
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                self.clocks.clear();
+                self.scheduler.set_alarms(Vec::new(), None);
+                self.status = Some(e);
+            }
+        }
+        self.profile = next.clone();
+        self.focus = 0;
+        // Saved profiles are always safe to edit, even if we started ephemeral.
+        self.persist = true;
+    }
+
+    fn add_clock(&mut self, tz: Tz) {
+        self.clocks.push(Clock { name: tz.name().to_string(), timezone: tz, pinned: false });
+        self.focus = self.clocks.len() - 1;
+        self.save_clocks();
+    }
+
+    fn remove_focused(&mut self) {
+        if self.focus < self.clocks.len() {
+            self.clocks.remove(self.focus);
+            self.focus = self.focus.min(self.clocks.len().saturating_sub(1));
+            self.save_clocks();
+        }
+    }
+
+    /// Moves the focused tile `delta` places, keeping it focused. Tiles do
+    /// not cross between the pinned and unpinned groups.
+    fn move_focused(&mut self, delta: isize) {
+        let target = self.focus as isize + delta;
+        if self.focus < self.clocks.len()
+            && target >= 0
+            && (target as usize) < self.clocks.len()
+            && self.clocks[target as usize].pinned == self.clocks[self.focus].pinned
+        {
+            self.clocks.swap(self.focus, target as usize);
+            self.focus = target as usize;
+            self.save_clocks();
+        }
+    }
+
+    fn cycle_focus(&mut self, delta: isize) {
+        let len = self.clocks.len() as isize;
+        if len > 0 {
+            self.focus = (self.focus as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Moves focus one tile in the grid, staying put at the edges.
+    fn navigate(&mut self, direction: KeyCode) {
+        let len = self.clocks.len();
+        if len == 0 {
+            return;
+        }
+        let cols = self.grid_cols.get();
+        let focus = self.focus;
+        self.focus = match direction {
+            KeyCode::Left if !focus.is_multiple_of(cols) => focus - 1,
+            KeyCode::Right if focus % cols + 1 < cols && focus + 1 < len => focus + 1,
+            KeyCode::Up if focus >= cols => focus - cols,
+            KeyCode::Down if focus + cols < len => focus + cols,
+            // The last row may be short; land on its final tile.
+            KeyCode::Down if focus / cols < (len - 1) / cols => len - 1,
+            _ => focus,
+        };
+    }
+
+    /// Pinned tiles are kept at the front of the grid, in pin order.
+    fn toggle_pin(&mut self) {
+        if self.focus >= self.clocks.len() {
+            return;
+        }
+        let mut clock = self.clocks.remove(self.focus);
+        clock.pinned = !clock.pinned;
+        let index = self.clocks.iter().filter(|c| c.pinned).count();
+        self.clocks.insert(index, clock);
+        self.focus = index;
+        self.save_clocks();
+    }
+
+    /// Sets the focused tile's label; an empty name goes back to the zone name.
+    fn rename_focused(&mut self, name: String) {
+        if let Some(clock) = self.clocks.get_mut(self.focus) {
+            let name = name.trim();
+            clock.name = if name.is_empty() { clock.timezone.name().to_string() } else { name.to_string() };
+            self.save_clocks();
+        }
+    }
+
+    /// Writes the current clock list to the active profile.
+    fn save_clocks(&mut self) {
+        if !self.persist {
+            self.status = Some("Not saved: clocks came from the command line (use --save)".to_string());
+            return;
+        }
+        let entries: Vec<ClockEntry> = self.clocks.iter().map(Clock::to_entry).collect();
+        let profile = self.profile.clone();
+        match config::update_config(|c| {
+            c.profile_mut(&profile).clocks = entries;
+            Ok::<_, config::ConfigError>(())
+        }) {
+            Ok(config) => {
+                self.config = config;
+                self.status = None;
+            }
+            Err(e) => self.status = Some(format!("Save failed: {}", e)),
+        }
+    }
+}
+
+pub fn run(
+    clocks: Vec<Clock>,
+    scheduler: Scheduler,
+    outputs: AlarmOutputs,
+    config: Config,
+    profile: &str,
+    persist: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -20,7 +213,20 @@ pub fn run(clocks: &[Clock], alarms: &[NaiveTime]) -> Result<(), Box<dyn std::er
     let mut terminal = Terminal::new(backend)?;
 
     // Run app
-    let res = run_app_loop(&mut terminal, clocks, alarms);
+    let mut app = App {
+        scheduler,
+        outputs,
+        missed: Vec::new(),
+        config,
+        profile: profile.to_string(),
+        clocks,
+        focus: 0,
+        grid_cols: Cell::new(1),
+        persist,
+        mode: Mode::Normal,
+        status: None,
+    };
+    let res = run_app_loop(&mut terminal, &mut app);
 
     // Restore terminal
     disable_raw_mode()?;
@@ -37,65 +243,411 @@ pub fn run(clocks: &[Clock], alarms: &[NaiveTime]) -> Result<(), Box<dyn std::er
     Ok(())
 }
 
-fn run_app_loop<B: Backend>(terminal: &mut Terminal<B>, clocks: &[Clock], alarms: &[NaiveTime]) -> io::Result<()> 
+fn run_app_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> 
 where
     std::io::Error: From<B::Error>,
 {
-    let mut dismissed_time: Option<NaiveTime> = None;
-
     loop {
-        let local_now = Local::now().time();
-        
-        // Reset dismissal if minute changed
-        if let Some(dismissed) = dismissed_time {
-            if local_now.hour() != dismissed.hour() || local_now.minute() != dismissed.minute() {
-                dismissed_time = None;
+        let events = app.scheduler.tick();
+        if let Some(failure) = app.outputs.handle(&mut app.scheduler, &events).pop() {
+            app.status = Some(failure);
+        }
+        for event in events {
+            match event {
+                // The banner shows what is ringing; the tile showing the
+                // alarm's zone takes focus.
+                AlarmEvent::Fired(alarm) => {
+                    if let Some(index) = app.clocks.iter().position(|c| Some(c.timezone) == alarm.zone) {
+                        app.focus = index;
+                    }
+                }
+                AlarmEvent::Missed(missed) => app.missed.push(missed),
+                AlarmEvent::Evaluated(at) => {
+                    if let Err(e) = config::save_last_evaluated(&app.profile, at) {
+                        app.status = Some(e.to_string());
+                    }
+                }
             }
         }
+        let now = app.scheduler.now();
+        let is_alarm_active = !app.scheduler.ringing(now).is_empty();
 
-        let is_alarm_active = alarms.iter().any(|&alarm| {
-            local_now.hour() == alarm.hour() && local_now.minute() == alarm.minute()
-        }) && dismissed_time.is_none();
-
-        terminal.draw(|f| ui(f, clocks, is_alarm_active))?;
+        terminal.draw(|f| ui(f, app, now))?;
 
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') => return Ok(()),
-                        KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => return Ok(()),
-                        KeyCode::Char(' ') | KeyCode::Char('d') => {
-                            if is_alarm_active {
-                                dismissed_time = Some(NaiveTime::from_hms_opt(local_now.hour(), local_now.minute(), 0).unwrap());
-                            }
+        if event::poll(Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(event::KeyModifiers::CONTROL);
+            if ctrl_c {
+                return Ok(());
+            }
+            if let Mode::AddClock(picker) = &mut app.mode {
+                match key.code {
+                    KeyCode::Esc => app.mode = Mode::Normal,
+                    KeyCode::Enter => {
+                        let chosen = picker.results.get(picker.selected).map(|m| m.tz);
+                        app.mode = Mode::Normal;
+                        if let Some(tz) = chosen {
+                            app.add_clock(tz);
                         }
-                        _ => {}
                     }
+                    KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+                    KeyCode::Down => {
+                        picker.selected = (picker.selected + 1).min(picker.results.len().saturating_sub(1));
+                    }
+                    KeyCode::Backspace => {
+                        picker.query.pop();
+                        picker.refresh();
+                    }
+                    KeyCode::Char(c) => {
+                        picker.query.push(c);
+                        picker.refresh();
+                    }
+                    _ => {}
                 }
+                continue;
+            }
+            if let Mode::Rename(input) = &mut app.mode {
+                match key.code {
+                    KeyCode::Esc => app.mode = Mode::Normal,
+                    KeyCode::Enter => {
+                        let name = std::mem::take(input);
+                        app.mode = Mode::Normal;
+                        app.rename_focused(name);
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) => input.push(c),
+                    _ => {}
+                }
+                continue;
+            }
+            if let Mode::Details = app.mode {
+                app.mode = Mode::Normal;
+                continue;
+            }
+
+            let keys = &app.config.keybindings;
+            let snooze = keys.snooze_for(app.config.alarm.snooze_minutes, |names| bound(names, key.code));
+            if bound(&keys.quit, key.code) {
+                return Ok(());
+            } else if bound(&keys.dismiss, key.code) && (is_alarm_active || !app.missed.is_empty()) {
+                app.scheduler.dismiss();
+                app.missed.clear();
+            } else if let Some(minutes) = snooze
+                && is_alarm_active
+            {
+                app.scheduler.snooze(minutes);
+            } else if bound(&keys.next_profile, key.code) {
+                app.next_profile();
+            } else if bound(&keys.add_clock, key.code) {
+                app.mode = Mode::AddClock(Picker::new());
+            } else if bound(&keys.remove_clock, key.code) {
+                app.remove_focused();
+            } else if bound(&keys.move_earlier, key.code) {
+                app.move_focused(-1);
+            } else if bound(&keys.move_later, key.code) {
+                app.move_focused(1);
+            } else if bound(&keys.focus_next, key.code) {
+                app.cycle_focus(1);
+            } else if bound(&keys.focus_prev, key.code) {
+                app.cycle_focus(-1);
+            } else if bound(&keys.left, key.code) {
+                app.navigate(KeyCode::Left);
+            } else if bound(&keys.right, key.code) {
+                app.navigate(KeyCode::Right);
+            } else if bound(&keys.up, key.code) {
+                app.navigate(KeyCode::Up);
+            } else if bound(&keys.down, key.code) {
+                app.navigate(KeyCode::Down);
+            } else if bound(&keys.details, key.code) && !app.clocks.is_empty() {
+                app.mode = Mode::Details;
+            } else if bound(&keys.rename, key.code) && let Some(clock) = app.clocks.get(app.focus) {
+                app.mode = Mode::Rename(clock.name.clone());
+            } else if bound(&keys.pin, key.code) {
+                app.toggle_pin();
+            }
+        }
+    }
+}
+
+/// Whether `code` matches any of the key names in a keybinding list.
+fn bound(names: &[String], code: KeyCode) -> bool {
+    names.iter().any(|name| key_from_name(name) == Some(code))
+}
+
+/// Single characters are matched case-sensitively ("J" is not "j"); named
+/// keys are not.
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let code = match name.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn ui(f: &mut Frame, app: &App, now: DateTime<Utc>) {
+    let mut size = f.area();
+
+    // Status bar: only worth the row when there is something to switch to or report.
+    let snoozed = app.scheduler.snoozed(now);
+    let missed = &app.missed;
+    if app.config.profiles.len() > 1 || app.status.is_some() || !snoozed.is_empty() || !missed.is_empty() {
+        let [grid, bar] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(size);
+        let mut spans = vec![match &app.status {
+            Some(status) => Span::styled(status.as_str(), Style::default().fg(Color::Red)),
+            None => Span::styled(format!(" profile: {}", app.profile), Style::default().fg(Color::DarkGray)),
+        }];
+        for (alarm, until) in snoozed {
+            let until = until.with_timezone(&Local).format("%H:%M");
+            spans.push(Span::styled(format!("  {} snoozed to {}", alarm.title(), until), Style::default().fg(Color::Yellow)));
+        }
+        for missed in missed {
+            spans.push(Span::styled(format!("  missed: {}", missed.describe(now)), Style::default().fg(Color::Magenta)));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)), bar);
+        size = grid;
+    }
+
+    let ringing = app.scheduler.ringing(now);
+    let arrangement = layout::arrange(app.clocks.len(), size.width, size.height);
+    app.grid_cols.set(arrangement.cols());
+    match arrangement {
+        Arrangement::Grid { cols, rows } => draw_grid(
+            f,
+            size,
+            (cols, rows),
+            &app.clocks,
+            &app.scheduler,
+            now,
+            app.focus,
+            !ringing.is_empty(),
+            &app.config.display,
+        ),
+        Arrangement::List => draw_list(
+            f,
+            size,
+            &app.clocks,
+            &app.scheduler,
+            now,
+            app.focus,
+            !ringing.is_empty(),
+            &app.config.display,
+        ),
+    }
+    if !ringing.is_empty() {
+        let keys = &app.config.keybindings;
+        let first = |names: &[String]| names.first().cloned().unwrap_or_default();
+        let mut hint = format!(
+            " Alarm ({}: dismiss, {}: snooze {}m",
+            first(&keys.dismiss),
+            first(&keys.snooze),
+            app.config.alarm.snooze_minutes
+        );
+        // e.g. ", 1/2/3: 5/10/15m" for whichever fixed snoozes have a key.
+        let (fixed_keys, fixed_minutes): (Vec<_>, Vec<_>) = keys
+            .fixed_snoozes()
+            .into_iter()
+            .filter_map(|(names, minutes)| Some((names.first()?.clone(), minutes.to_string())))
+            .unzip();
+        if !fixed_keys.is_empty() {
+            hint.push_str(&format!(", {}: {}m", fixed_keys.join("/"), fixed_minutes.join("/")));
+        }
+        hint.push_str(") ");
+        draw_alarm_banner(f, size, &ringing, &hint);
+    }
+
+    match &app.mode {
+        Mode::AddClock(picker) => draw_picker(f, size, picker),
+        Mode::Rename(input) => draw_rename(f, size, input),
+        Mode::Details => {
+            if let Some(clock) = app.clocks.get(app.focus) {
+                draw_details(f, size, clock, now);
             }
         }
+        Mode::Normal => {}
+    }
+}
+
+/// A `width` x `height` rectangle centered in `size`, shrunk to fit.
+fn centered(size: Rect, width: u16, height: u16) -> Rect {
+    let width = size.width.min(width);
+    let height = size.height.min(height);
+    Rect {
+        x: size.x + (size.width - width) / 2,
+        y: size.y + (size.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Overlay along the top of the grid naming each ringing alarm, with its
+/// message underneath when it has one.
+fn draw_alarm_banner(f: &mut Frame, size: Rect, ringing: &[&Alarm], title: &str) {
+    let mut lines = Vec::new();
+    for alarm in ringing {
+        let schedule = match alarm.zone {
+            Some(tz) => format!("{} {}", alarm.summary(), tz.name()),
+            None => alarm.summary(),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(alarm.title(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("  {}", schedule), Style::default().fg(Color::DarkGray)),
+        ]));
+        if let Some(message) = &alarm.message {
+            lines.push(Line::from(Span::styled(message.as_str(), Style::default().fg(Color::White))));
+        }
+    }
+    let width = size.width.min(70);
+    let area = Rect {
+        x: size.x + (size.width - width) / 2,
+        y: size.y + 1.min(size.height),
+        width,
+        height: (lines.len() as u16 + 2).min(size.height.saturating_sub(1)),
+    };
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(Color::Red))
+        .title(title);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_rename(f: &mut Frame, size: Rect, input: &str) {
+    let area = centered(size, 50, 3);
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Rename (Enter: save, Esc: cancel, empty: zone name) ");
+    let text = Line::from(vec![
+        Span::styled("> ", Style::default().fg(Color::DarkGray)),
+        Span::styled(input, Style::default().fg(Color::Yellow)),
+    ]);
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn draw_details(f: &mut Frame, size: Rect, clock: &Clock, now: DateTime<Utc>) {
+    let local_offset = now.with_timezone(&Local).offset().local_minus_utc();
+    let now = now.with_timezone(&clock.timezone);
+    let offset = now.offset().fix().local_minus_utc();
+    let dst = now.offset().dst_offset().num_seconds() != 0;
+
+    let row = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{:<12}", label), Style::default().fg(Color::DarkGray)),
+            Span::raw(value),
+        ])
+    };
+    let mut lines = vec![
+        row("Zone", clock.timezone.name().to_string()),
+        row("Label", clock.subtitle().map_or("-".to_string(), |_| clock.name.clone())),
+        row("Time", now.format("%H:%M:%S").to_string()),
+        row("Date", now.format("%A %Y-%m-%d").to_string()),
+        row("UTC offset", format!("{} ({})", format_offset(offset), now.format("%Z"))),
+        row("vs. local", format_offset(offset - local_offset)),
+        row("DST", if dst { "in effect" } else { "not in effect" }.to_string()),
+    ];
+    if clock.pinned {
+        lines.push(row("Pinned", "yes".to_string()));
+    }
+
+    let area = centered(size, 44, lines.len() as u16 + 2);
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} ", clock.name));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Formats an offset in seconds as "+05:30".
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
+
+/// Offset from UTC and the zone's abbreviation, e.g. "UTC+09:00 JST". Zones
+/// without an abbreviation of their own get the offset alone.
+fn describe_offset(time: &DateTime<Tz>) -> String {
+    let offset = format_offset(time.offset().fix().local_minus_utc());
+    let abbreviation = time.format("%Z").to_string();
+    if abbreviation.starts_with(['+', '-']) {
+        format!("UTC{}", offset)
+    } else {
+        format!("UTC{} {}", offset, abbreviation)
     }
 }
 
-fn ui(f: &mut Frame, clocks: &[Clock], is_alarm_active: bool) {
-    let size = f.area();
+/// Centered modal listing zones that match the typed query.
+fn draw_picker(f: &mut Frame, size: Rect, picker: &Picker) {
+    let area = centered(size, 60, 20);
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Add clock (Enter: add, Esc: cancel) ");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let [input, list] = Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(inner);
+    f.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("> ", Style::default().fg(Color::DarkGray)),
+            Span::styled(picker.query.as_str(), Style::default().fg(Color::Yellow)),
+        ])),
+        input,
+    );
+
+    let items: Vec<ListItem> = picker.results.iter().map(|m| ListItem::new(m.describe())).collect();
+    let list_widget = List::new(items)
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    f.render_stateful_widget(list_widget, list, &mut state);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_grid(
+    f: &mut Frame,
+    size: Rect,
+    (cols, rows): (usize, usize),
+    clocks: &[Clock],
+    scheduler: &Scheduler,
+    now: DateTime<Utc>,
+    focus: usize,
+    is_alarm_active: bool,
+    display: &DisplayPrefs,
+) {
     let clock_count = clocks.len();
     
     if clock_count == 0 {
+        let hint = Paragraph::new("No clocks. Press 'a' to add one.")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray));
+        f.render_widget(hint, size);
         return;
     }
 
-    // Simple grid layout logic
-    // Calculate columns and rows based on count to try and keep it square-ish
-    let cols = (clock_count as f64).sqrt().ceil() as usize;
-    let rows = (clock_count as f64 / cols as f64).ceil() as usize;
-
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
-            std::iter::repeat(Constraint::Ratio(1, rows as u32))
-                .take(rows)
+            std::iter::repeat_n(Constraint::Ratio(1, rows as u32), rows)
                 .collect::<Vec<_>>(),
         )
         .split(size);
@@ -111,8 +663,7 @@ fn ui(f: &mut Frame, clocks: &[Clock], is_alarm_active: bool) {
         let row_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints(
-                std::iter::repeat(Constraint::Ratio(1, cols as u32))
-                    .take(cols)
+                std::iter::repeat_n(Constraint::Ratio(1, cols as u32), cols)
                     .collect::<Vec<_>>(),
             )
             .split(chunks[row]);
@@ -123,29 +674,55 @@ fn ui(f: &mut Frame, clocks: &[Clock], is_alarm_active: bool) {
 
         let area = row_chunks[col];
         
-        let time = Utc::now().with_timezone(&clock.timezone);
-        let time_str = time.format("%H:%M:%S").to_string();
-        let date_str = time.format("%Y-%m-%d").to_string();
+        let time = now.with_timezone(&clock.timezone);
+        let time_str = time.format(&display.time_format).to_string();
 
-        let text = vec![
-            Line::from(Span::styled(
+        // The time always shows; the other lines, and block digits, only as
+        // far as the tile has room for them.
+        let mut wanted = vec![TileLine::Date];
+        if clock.subtitle().is_some() {
+            wanted.push(TileLine::Zone);
+        }
+        wanted.extend([TileLine::Name, TileLine::Offset]);
+        let inner = area.inner(Margin::new(1, 1));
+        let content = layout::fit_tile(
+            &time_str,
+            &wanted,
+            inner.width.saturating_sub(2) as usize,
+            inner.height as usize,
+            display.big_digits,
+        );
+        let shows = |line: TileLine| content.lines.contains(&line);
+
+        let time_style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+        let mut text = Vec::new();
+        if shows(TileLine::Name) {
+            text.push(Line::from(Span::styled(
                 &clock.name,
                 Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
-            )),
-            Line::from(""),
-            Line::from(Span::styled(
-                time_str,
-                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD), // font_size isn't real in TUI
-            )),
-            Line::from(Span::styled(
-                date_str,
+            )));
+        }
+        if let Some(zone) = clock.subtitle().filter(|_| shows(TileLine::Zone)) {
+            text.push(Line::from(Span::styled(zone, Style::default().fg(Color::DarkGray))));
+        }
+        match content.big {
+            Some(lines) => text.extend(lines.into_iter().map(|line| Line::from(Span::styled(line, time_style)))),
+            None => text.push(Line::from(Span::styled(time_str, time_style))),
+        }
+        if shows(TileLine::Date) {
+            text.push(Line::from(Span::styled(
+                time.format(&display.date_format).to_string(),
                 Style::default().fg(Color::Gray),
-            )),
-        ];
+            )));
+        }
+        if shows(TileLine::Offset) {
+            text.push(Line::from(Span::styled(describe_offset(&time), Style::default().fg(Color::DarkGray))));
+        }
+        let content_height = text.len() as u16;
 
-        let paragraph = Paragraph::new(text)
-            .alignment(Alignment::Center)
-            .wrap(ratatui::widgets::Wrap { trim: true });
+        // Not wrapped: `fit_tile` budgeted one row per line, so anything too
+        // wide is cut at the border instead.
+        let paragraph = Paragraph::new(text).alignment(Alignment::Center);
 
         // Centering vertically is a bit manual in basic TUI without Flex, 
         // but let's just render the paragraph in the block.
@@ -153,7 +730,6 @@ fn ui(f: &mut Frame, clocks: &[Clock], is_alarm_active: bool) {
         // simplified here to just fill the block.
         
         // Let's try to center it vertically by calculating padding
-        let content_height = 4; // 4 lines of text
         let block_height = area.height.saturating_sub(2); // minus borders
         let v_padding = block_height.saturating_sub(content_height) / 2;
         
@@ -164,21 +740,121 @@ fn ui(f: &mut Frame, clocks: &[Clock], is_alarm_active: bool) {
                 Constraint::Length(content_height),
                 Constraint::Min(0),
             ])
-            .split(area)[1];
+            .split(inner)[1];
 
         f.render_widget(paragraph, inner_area);
         
+        let focused = i == focus && clock_count > 1;
         let border_color = if is_alarm_active {
             Color::Red
+        } else if focused {
+            Color::LightCyan
         } else {
             Color::White
         };
 
-        let block = Block::default()
+        let mut block = Block::default()
             .borders(Borders::ALL)
-            .title(clock.name.clone())
+            .border_type(if focused { BorderType::Thick } else { BorderType::Plain })
+            .title(if clock.pinned { format!("* {}", clock.name) } else { clock.name.clone() })
             .border_style(Style::default().fg(border_color));
 
+        // Alarms set in this tile's zone are listed along its bottom edge.
+        let ringing = scheduler.ringing(now);
+        let zone_alarms: Vec<Span> = scheduler
+            .alarms()
+            .iter()
+            .filter(|alarm| alarm.zone == Some(clock.timezone))
+            .map(|alarm| {
+                let name = alarm.label.as_deref().unwrap_or("alarm");
+                let text = format!(" {} {} ", name, alarm.short());
+                if ringing.iter().any(|r| r.id == alarm.id) {
+                    Span::styled(text, Style::default().fg(Color::Red))
+                } else if let Some(until) = scheduler.snoozed_until(alarm, now) {
+                    let until = until.with_timezone(&clock.timezone).format("%H:%M");
+                    Span::styled(format!("{}(snoozed to {}) ", text, until), Style::default().fg(Color::Yellow))
+                } else {
+                    Span::styled(text, Style::default().fg(Color::DarkGray))
+                }
+            })
+            .collect();
+        if !zone_alarms.is_empty() {
+            block = block.title_bottom(Line::from(zone_alarms).right_aligned());
+        }
+
         f.render_widget(block, area);
     }
 }
+
+/// One line per clock, for terminals too short for tiles: name, time, then
+/// the date, offset and alarms in this zone as far as the width allows.
+#[allow(clippy::too_many_arguments)]
+fn draw_list(
+    f: &mut Frame,
+    size: Rect,
+    clocks: &[Clock],
+    scheduler: &Scheduler,
+    now: DateTime<Utc>,
+    focus: usize,
+    is_alarm_active: bool,
+    display: &DisplayPrefs,
+) {
+    let name_width = clocks
+        .iter()
+        .map(|clock| clock.name.chars().count() + if clock.pinned { 2 } else { 0 })
+        .max()
+        .unwrap_or(0);
+    // Scroll just far enough to keep the focused clock on screen.
+    let visible = size.height as usize;
+    let first = focus.saturating_sub(visible.saturating_sub(1));
+    let ringing = scheduler.ringing(now);
+
+    for (row, (i, clock)) in clocks.iter().enumerate().skip(first).take(visible).enumerate() {
+        let time = now.with_timezone(&clock.timezone);
+        let time_str = time.format(&display.time_format).to_string();
+        let date_str = time.format(&display.date_format).to_string();
+        let offset_str = describe_offset(&time);
+        let alarms: Vec<&Alarm> = scheduler
+            .alarms()
+            .iter()
+            .filter(|alarm| alarm.zone == Some(clock.timezone))
+            .collect();
+        let alarms_str = alarms
+            .iter()
+            .map(|alarm| format!("{} {}", alarm.label.as_deref().unwrap_or("alarm"), alarm.short()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let name = if clock.pinned { format!("* {}", clock.name) } else { clock.name.clone() };
+        let focused = i == focus && clocks.len() > 1;
+        let name_style = if focused {
+            Style::default().fg(Color::LightCyan).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        };
+        let time_color = if is_alarm_active { Color::Red } else { Color::Cyan };
+        let mut spans = vec![
+            Span::styled(format!("{:<width$}", name, width = name_width), name_style),
+            Span::raw("  "),
+            Span::styled(time_str.clone(), Style::default().fg(time_color).add_modifier(Modifier::BOLD)),
+        ];
+
+        let mut optional = vec![
+            (date_str, Style::default().fg(Color::Gray)),
+            (offset_str, Style::default().fg(Color::DarkGray)),
+        ];
+        if !alarms.is_empty() {
+            let rings = alarms.iter().any(|alarm| ringing.iter().any(|r| r.id == alarm.id));
+            optional.push((alarms_str, Style::default().fg(if rings { Color::Red } else { Color::DarkGray })));
+        }
+        let widths: Vec<usize> = optional.iter().map(|(text, _)| text.chars().count()).collect();
+        let fits = layout::fit_columns(name_width + 2 + time_str.chars().count(), &widths, size.width as usize);
+        for (text, style) in optional.into_iter().take(fits) {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(text, style));
+        }
+
+        let line = Rect { y: size.y + row as u16, height: 1, ..size };
+        f.render_widget(Paragraph::new(Line::from(spans)), line);
+    }
+}
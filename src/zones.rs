@@ -0,0 +1,422 @@
+/*
+ * Time zone lookup.
+ *
+ * Resolves user input to a `Tz`: an embedded table of cities, countries and
+ * abbreviations first, so that "EST" means New York rather than the
+ * fixed-offset IANA zone of that name, then exact IANA identifiers, then the
+ * city part of every IANA name. When nothing matches outright, a ranked list
+ * of fuzzy suggestions is returned.
+ */
+
+use chrono_tz::{TZ_VARIANTS, Tz};
+use std::{collections::HashSet, fmt};
+
+/// Cities, countries and abbreviations that are not obvious from the IANA
+/// names themselves. A key may map to several zones (e.g. "IST"); such a
+/// query is reported as ambiguous rather than silently picking one.
+const ALIASES: &[(&str, &str)] = &[
+    // North America
+    ("san francisco", "America/Los_Angeles"),
+    ("sf", "America/Los_Angeles"),
+    ("seattle", "America/Los_Angeles"),
+    ("portland", "America/Los_Angeles"),
+    ("las vegas", "America/Los_Angeles"),
+    ("san diego", "America/Los_Angeles"),
+    ("la", "America/Los_Angeles"),
+    ("salt lake city", "America/Denver"),
+    ("dallas", "America/Chicago"),
+    ("houston", "America/Chicago"),
+    ("austin", "America/Chicago"),
+    ("minneapolis", "America/Chicago"),
+    ("boston", "America/New_York"),
+    ("washington", "America/New_York"),
+    ("dc", "America/New_York"),
+    ("miami", "America/New_York"),
+    ("atlanta", "America/New_York"),
+    ("philadelphia", "America/New_York"),
+    ("nyc", "America/New_York"),
+    ("montreal", "America/Toronto"),
+    ("ottawa", "America/Toronto"),
+    ("calgary", "America/Edmonton"),
+    ("honolulu", "Pacific/Honolulu"),
+    ("hawaii", "Pacific/Honolulu"),
+    ("alaska", "America/Anchorage"),
+    // South America
+    ("rio de janeiro", "America/Sao_Paulo"),
+    ("rio", "America/Sao_Paulo"),
+    ("brasilia", "America/Sao_Paulo"),
+    // Europe
+    ("edinburgh", "Europe/London"),
+    ("manchester", "Europe/London"),
+    ("frankfurt", "Europe/Berlin"),
+    ("munich", "Europe/Berlin"),
+    ("hamburg", "Europe/Berlin"),
+    ("geneva", "Europe/Zurich"),
+    ("milan", "Europe/Rome"),
+    ("barcelona", "Europe/Madrid"),
+    ("st petersburg", "Europe/Moscow"),
+    ("saint petersburg", "Europe/Moscow"),
+    ("krakow", "Europe/Warsaw"),
+    // Africa and the Middle East
+    ("cape town", "Africa/Johannesburg"),
+    ("tel aviv", "Asia/Jerusalem"),
+    ("abu dhabi", "Asia/Dubai"),
+    ("doha", "Asia/Qatar"),
+    // Asia and Oceania
+    ("bangalore", "Asia/Kolkata"),
+    ("bengaluru", "Asia/Kolkata"),
+    ("mumbai", "Asia/Kolkata"),
+    ("bombay", "Asia/Kolkata"),
+    ("delhi", "Asia/Kolkata"),
+    ("new delhi", "Asia/Kolkata"),
+    ("chennai", "Asia/Kolkata"),
+    ("hyderabad", "Asia/Kolkata"),
+    ("pune", "Asia/Kolkata"),
+    ("calcutta", "Asia/Kolkata"),
+    ("lahore", "Asia/Karachi"),
+    ("islamabad", "Asia/Karachi"),
+    ("beijing", "Asia/Shanghai"),
+    ("shenzhen", "Asia/Shanghai"),
+    ("guangzhou", "Asia/Shanghai"),
+    ("osaka", "Asia/Tokyo"),
+    ("kyoto", "Asia/Tokyo"),
+    ("busan", "Asia/Seoul"),
+    ("hanoi", "Asia/Bangkok"),
+    ("saigon", "Asia/Ho_Chi_Minh"),
+    ("canberra", "Australia/Sydney"),
+    ("wellington", "Pacific/Auckland"),
+    // Countries
+    ("usa", "America/New_York"),
+    ("usa", "America/Chicago"),
+    ("usa", "America/Denver"),
+    ("usa", "America/Los_Angeles"),
+    ("united states", "America/New_York"),
+    ("united states", "America/Chicago"),
+    ("united states", "America/Denver"),
+    ("united states", "America/Los_Angeles"),
+    ("canada", "America/Toronto"),
+    ("canada", "America/Winnipeg"),
+    ("canada", "America/Edmonton"),
+    ("canada", "America/Vancouver"),
+    ("australia", "Australia/Sydney"),
+    ("australia", "Australia/Adelaide"),
+    ("australia", "Australia/Brisbane"),
+    ("australia", "Australia/Perth"),
+    ("uk", "Europe/London"),
+    ("united kingdom", "Europe/London"),
+    ("england", "Europe/London"),
+    ("britain", "Europe/London"),
+    ("scotland", "Europe/London"),
+    ("ireland", "Europe/Dublin"),
+    ("france", "Europe/Paris"),
+    ("germany", "Europe/Berlin"),
+    ("spain", "Europe/Madrid"),
+    ("italy", "Europe/Rome"),
+    ("netherlands", "Europe/Amsterdam"),
+    ("switzerland", "Europe/Zurich"),
+    ("sweden", "Europe/Stockholm"),
+    ("russia", "Europe/Moscow"),
+    ("india", "Asia/Kolkata"),
+    ("china", "Asia/Shanghai"),
+    ("korea", "Asia/Seoul"),
+    ("south korea", "Asia/Seoul"),
+    ("taiwan", "Asia/Taipei"),
+    ("philippines", "Asia/Manila"),
+    ("indonesia", "Asia/Jakarta"),
+    ("vietnam", "Asia/Ho_Chi_Minh"),
+    ("thailand", "Asia/Bangkok"),
+    ("uae", "Asia/Dubai"),
+    ("united arab emirates", "Asia/Dubai"),
+    ("saudi arabia", "Asia/Riyadh"),
+    ("south africa", "Africa/Johannesburg"),
+    ("nigeria", "Africa/Lagos"),
+    ("kenya", "Africa/Nairobi"),
+    ("brazil", "America/Sao_Paulo"),
+    ("argentina", "America/Argentina/Buenos_Aires"),
+    ("mexico", "America/Mexico_City"),
+    ("colombia", "America/Bogota"),
+    ("new zealand", "Pacific/Auckland"),
+    // Abbreviations
+    ("pt", "America/Los_Angeles"),
+    ("pst", "America/Los_Angeles"),
+    ("pdt", "America/Los_Angeles"),
+    ("mt", "America/Denver"),
+    ("mst", "America/Denver"),
+    ("mst", "America/Phoenix"),
+    ("mdt", "America/Denver"),
+    ("ct", "America/Chicago"),
+    ("cst", "America/Chicago"),
+    ("cst", "Asia/Shanghai"),
+    ("cdt", "America/Chicago"),
+    ("et", "America/New_York"),
+    ("est", "America/New_York"),
+    ("edt", "America/New_York"),
+    ("akst", "America/Anchorage"),
+    ("hst", "Pacific/Honolulu"),
+    ("bst", "Europe/London"),
+    ("wet", "Europe/Lisbon"),
+    ("cet", "Europe/Paris"),
+    ("cest", "Europe/Paris"),
+    ("eet", "Europe/Athens"),
+    ("eest", "Europe/Athens"),
+    ("ist", "Asia/Kolkata"),
+    ("ist", "Asia/Jerusalem"),
+    ("ist", "Europe/Dublin"),
+    ("jst", "Asia/Tokyo"),
+    ("kst", "Asia/Seoul"),
+    ("hkt", "Asia/Hong_Kong"),
+    ("sgt", "Asia/Singapore"),
+    ("pht", "Asia/Manila"),
+    ("wib", "Asia/Jakarta"),
+    ("gst", "Asia/Dubai"),
+    ("msk", "Europe/Moscow"),
+    ("sast", "Africa/Johannesburg"),
+    ("wat", "Africa/Lagos"),
+    ("eat", "Africa/Nairobi"),
+    ("brt", "America/Sao_Paulo"),
+    ("aest", "Australia/Sydney"),
+    ("aedt", "Australia/Sydney"),
+    ("acst", "Australia/Adelaide"),
+    ("awst", "Australia/Perth"),
+    ("nzst", "Pacific/Auckland"),
+    ("nzdt", "Pacific/Auckland"),
+];
+
+/// How many suggestions an unresolved query reports.
+const SUGGESTIONS: usize = 5;
+
+#[derive(Debug)]
+pub enum ResolveError {
+    /// Nothing matched closely enough to resolve; `suggestions` may be empty.
+    Unknown { query: String, suggestions: Vec<Match> },
+    /// An alias names several zones, e.g. "IST" or "USA".
+    Ambiguous { query: String, candidates: Vec<Tz> },
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (list, names): (&str, Vec<String>) = match self {
+            ResolveError::Unknown { query, suggestions } => {
+                write!(f, "Invalid time zone: {}", query)?;
+                ("Did you mean", suggestions.iter().map(Match::describe).collect())
+            }
+            ResolveError::Ambiguous { query, candidates } => {
+                write!(f, "Ambiguous time zone: {}", query)?;
+                ("It could be", candidates.iter().map(|tz| tz.name().to_string()).collect())
+            }
+        };
+        if !names.is_empty() {
+            write!(f, "\n{}: {}", list, names.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+/// A search hit: the zone and the name or alias that matched the query.
+#[derive(Clone, Debug)]
+pub struct Match {
+    pub tz: Tz,
+    pub matched: &'static str,
+    pub score: u32,
+}
+
+impl Match {
+    /// The zone name, followed by the alias that found it if that differs:
+    /// "Asia/Kolkata (bangalore)".
+    pub fn describe(&self) -> String {
+        let name = self.tz.name();
+        if self.matched == name || self.matched == city(name) {
+            name.to_string()
+        } else {
+            format!("{} ({})", name, self.matched)
+        }
+    }
+}
+
+/// Resolves a zone name, city, country or abbreviation to a single zone.
+pub fn resolve(query: &str) -> Result<Tz, ResolveError> {
+    let query = query.trim();
+    let key = normalize(query);
+    let mut aliased: Vec<Tz> = ALIASES
+        .iter()
+        .filter(|(alias, _)| *alias == key)
+        .filter_map(|(_, zone)| zone.parse::<Tz>().ok())
+        .collect();
+    aliased.dedup();
+    match aliased.len() {
+        0 => {}
+        1 => return Ok(aliased[0]),
+        _ => return Err(ResolveError::Ambiguous { query: query.to_string(), candidates: aliased }),
+    }
+    if let Ok(tz) = query.parse::<Tz>() {
+        return Ok(tz);
+    }
+
+    // Case-insensitive full name, then the city part ("new york", "Tokyo").
+    // Cities that appear more than once are links to the same zone (e.g.
+    // "America/Indianapolis"), so any of them will do; take the shortest.
+    let exact = TZ_VARIANTS
+        .iter()
+        .copied()
+        .filter(|tz| normalize(tz.name()) == key || normalize(city(tz.name())) == key)
+        .min_by_key(|tz| tz.name().len());
+    if let Some(tz) = exact {
+        return Ok(tz);
+    }
+
+    let suggestions = search(query, SUGGESTIONS);
+    Err(ResolveError::Unknown { query: query.to_string(), suggestions })
+}
+
+/// Ranks zones against `query`, best first, one entry per zone. An empty
+/// query returns every zone in alphabetical order.
+pub fn search(query: &str, limit: usize) -> Vec<Match> {
+    let key = normalize(query);
+    let candidates = TZ_VARIANTS
+        .iter()
+        .flat_map(|tz| [(*tz, tz.name()), (*tz, city(tz.name()))])
+        .chain(
+            ALIASES
+                .iter()
+                .filter_map(|(alias, zone)| zone.parse::<Tz>().ok().map(|tz| (tz, *alias))),
+        );
+
+    let mut matches: Vec<Match> = candidates
+        .filter_map(|(tz, name)| score(&key, &normalize(name)).map(|score| Match { tz, matched: name, score }))
+        .collect();
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.tz.name().cmp(b.tz.name())));
+
+    let mut seen = HashSet::new();
+    matches.retain(|m| seen.insert(m.tz));
+    matches.truncate(limit);
+    matches
+}
+
+/// Higher is better; `None` means "not a plausible match".
+fn score(query: &str, candidate: &str) -> Option<u32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    if candidate == query {
+        return Some(1000);
+    }
+    if candidate.starts_with(query) {
+        return Some(800 - (candidate.len() - query.len()).min(100) as u32);
+    }
+    if let Some(pos) = candidate.find(query) {
+        // Prefer hits at a word boundary ("york" in "new york").
+        let boundary = candidate[..pos].ends_with([' ', '/']);
+        return Some(if boundary { 700 } else { 600 } - pos.min(100) as u32);
+    }
+    let distance = edit_distance(query, candidate);
+    let allowed = (query.chars().count() / 3).max(1);
+    if distance <= allowed {
+        return Some(500 - distance as u32 * 50);
+    }
+    if is_subsequence(query, candidate) {
+        return Some(200 - (candidate.len() - query.len()).min(100) as u32);
+    }
+    None
+}
+
+/// Lowercases and treats `_`, `-` and runs of spaces alike, so "New_York",
+/// "new-york" and "new  york" compare equal.
+fn normalize(s: &str) -> String {
+    s.to_lowercase()
+        .replace(['_', '-'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The last component of an IANA name: "America/Argentina/Buenos_Aires" -> "Buenos_Aires".
+fn city(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|c| chars.any(|h| h == c))
+}
+
+/// Edits (insertions, deletions, substitutions and swaps of neighbouring
+/// characters) needed to turn `a` into `b`, so "nwe yrok" is two away from
+/// "new york".
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Rows for the prefixes of `a` two, one and zero characters shorter.
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (previous[j] + 1).min(row[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(before[j - 2] + 1);
+            }
+        }
+        before = std::mem::replace(&mut previous, row);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{America, Asia};
+
+    #[test]
+    fn resolves_names_cities_and_aliases() {
+        let cases = [
+            ("Asia/Tokyo", Asia::Tokyo),
+            ("tokyo", Asia::Tokyo),
+            ("new york", America::New_York),
+            ("New_York", America::New_York),
+            ("bangalore", Asia::Kolkata),
+            ("PST", America::Los_Angeles),
+            ("EST", America::New_York),
+            ("est", America::New_York),
+            ("CET", chrono_tz::Europe::Paris),
+            ("UTC", chrono_tz::UTC),
+        ];
+        for (query, zone) in cases {
+            assert_eq!(resolve(query).unwrap(), zone, "{}", query);
+        }
+    }
+
+    #[test]
+    fn shared_abbreviations_are_ambiguous() {
+        match resolve("IST") {
+            Err(ResolveError::Ambiguous { candidates, .. }) => {
+                assert_eq!(candidates, [Asia::Kolkata, Asia::Jerusalem, chrono_tz::Europe::Dublin]);
+            }
+            other => panic!("expected IST to be ambiguous, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn typos_get_suggestions() {
+        for query in ["nwe_yrok", "new yrok", "tokoy"] {
+            match resolve(query) {
+                Err(ResolveError::Unknown { suggestions, .. }) => {
+                    assert!(!suggestions.is_empty(), "{}", query);
+                }
+                other => panic!("expected no match for {}, got {:?}", query, other),
+            }
+        }
+        let best = |query| search(query, 1)[0].tz;
+        assert_eq!(best("nwe_yrok"), America::New_York);
+        assert_eq!(best("tokoy"), Asia::Tokyo);
+    }
+
+    #[test]
+    fn edit_distance_counts_swaps_as_one() {
+        assert_eq!(edit_distance("nwe yrok", "new york"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}